@@ -96,7 +96,7 @@ fn main() {
     });
 
     let events = game.events();
-    let prepared = elikar_egui::build(&mut game, events);
+    let prepared = elikar_egui::build(&mut game, events).unwrap();
 
     game.spawn_local(async move{
         let mut prepared = prepared;
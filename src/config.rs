@@ -0,0 +1,193 @@
+use std::path::Path;
+use egui::{FontData, FontDefinitions};
+use elikar::{common::Spawner, events::Events};
+use xecs::entity::EntityId;
+use crate::{Error, GamepadBindings, KeyRepeat, Prepared, fonts};
+
+/// How far a wheel tick scrolls
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum WheelScroll {
+    Points(f32),
+    /// lines of body text
+    Lines(f32),
+    /// fractions of the screen
+    Pages(f32),
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum RenderOrder {
+    /// egui acquires,draws and presents the frame by itself in render stage
+    OnRender,
+    /// egui waits for the frame handed over by `LastPass::present`,
+    /// draws on top of it and presents it
+    LastPass,
+    /// egui never touches the surface,
+    /// paint it into your own frame with `EguiPainter::paint`
+    Manual,
+}
+
+/// All the settings `build` used to hard-code
+#[derive(Clone)]
+pub struct EguiConfig {
+    /// fonts set to the context before the first frame
+    pub fonts : FontDefinitions,
+    /// msaa sample count of the render pass,must match the surface
+    pub msaa_samples : u32,
+    /// color the surface is cleared to before drawing egui,`None` draws on top of the frame.
+    /// `None` needs `RenderOrder::LastPass` or `Manual`,
+    /// the texture acquired by `OnRender` has undefined contents
+    pub clear_color : Option<wgpu::Color>,
+    /// physical pixels per egui point,derived from the window if `None`
+    pub scale_factor : Option<f32>,
+    /// distance scrolled per wheel tick
+    pub wheel_scroll : WheelScroll,
+    /// when the egui pass runs relative to other render systems
+    pub render_order : RenderOrder,
+    /// size of the IME candidate area,in points.
+    /// `None` takes a caret as tall as a row of body text
+    pub ime_size : Option<(f32,f32)>,
+    /// how held keys repeat
+    pub key_repeat : KeyRepeat,
+    /// translate touch into pointer events,zoom and scroll by ourselves,
+    /// sdl stops emulating the mouse with touch for the whole app
+    pub touch : bool,
+    /// navigate egui with controllers,disabled if `None`
+    pub gamepad : Option<GamepadBindings>,
+    /// the window egui lives in,build once per window for several windows.
+    /// Its surface and configuration are components of the window,
+    /// `EguiPainter`,`EguiSettings` and the other states are attached to it.
+    /// `None` takes the first window and keeps all of them as world resources
+    pub window : Option<EntityId>,
+}
+
+impl Default for EguiConfig {
+    fn default() -> Self {
+        EguiConfig {
+            fonts: fonts::default_fonts(),
+            msaa_samples: 1,
+            clear_color: Some(wgpu::Color::WHITE),
+            scale_factor: None,
+            wheel_scroll: WheelScroll::Points(8.0),
+            render_order: RenderOrder::OnRender,
+            ime_size: None,
+            key_repeat: KeyRepeat::Os,
+            touch: false,
+            gamepad: None,
+            window: None,
+        }
+    }
+}
+
+#[derive(Default,Clone)]
+pub struct EguiBuilder {
+    config : EguiConfig
+}
+
+impl EguiBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config : EguiConfig) -> Self {
+        EguiBuilder { config }
+    }
+
+    pub fn fonts(mut self,fonts : FontDefinitions) -> Self {
+        self.config.fonts = fonts;
+        self
+    }
+
+    /// Append a fallback font to all the families
+    pub fn font(mut self,name : &str,data : FontData) -> Self {
+        fonts::append_fallback(&mut self.config.fonts, name, data);
+        self
+    }
+
+    /// Load a font file and append it as a fallback to all the families
+    pub fn font_file<P : AsRef<Path>>(self,name : &str,path : P) -> Result<Self,Error> {
+        let data = fonts::load(path).map_err(Error::Io)?;
+        Ok(self.font(name, data))
+    }
+
+    pub fn msaa_samples(mut self,samples : u32) -> Self {
+        self.config.msaa_samples = samples;
+        self
+    }
+
+    pub fn clear_color(mut self,color : Option<wgpu::Color>) -> Self {
+        self.config.clear_color = color;
+        self
+    }
+
+    /// Keep the contents of the frame and draw egui on top of it.
+    /// It implies `last_pass` unless painting manually,
+    /// a freshly acquired surface texture has nothing to keep
+    pub fn overlay(mut self) -> Self {
+        if self.config.render_order == RenderOrder::OnRender {
+            self.config.render_order = RenderOrder::LastPass;
+        }
+        self.clear_color(None)
+    }
+
+    /// Draw egui as an overlay after all other render systems,
+    /// they hand the frame over through the `LastPass` resource instead of presenting it
+    pub fn last_pass(mut self) -> Self {
+        self.config.render_order = RenderOrder::LastPass;
+        self.clear_color(None)
+    }
+
+    /// Don't spawn the renderer task,paint egui with the `EguiPainter` resource
+    pub fn manual(mut self) -> Self {
+        self.config.render_order = RenderOrder::Manual;
+        self
+    }
+
+    pub fn scale_factor(mut self,scale_factor : f32) -> Self {
+        self.config.scale_factor = Some(scale_factor);
+        self
+    }
+
+    /// Points scrolled per wheel tick
+    pub fn wheel_multiplier(self,multiplier : f32) -> Self {
+        self.wheel_scroll(WheelScroll::Points(multiplier))
+    }
+
+    pub fn wheel_scroll(mut self,wheel_scroll : WheelScroll) -> Self {
+        self.config.wheel_scroll = wheel_scroll;
+        self
+    }
+
+    pub fn ime_size(mut self,width : f32,height : f32) -> Self {
+        self.config.ime_size = Some((width,height));
+        self
+    }
+
+    pub fn key_repeat(mut self,key_repeat : KeyRepeat) -> Self {
+        self.config.key_repeat = key_repeat;
+        self
+    }
+
+    pub fn touch(mut self,touch : bool) -> Self {
+        self.config.touch = touch;
+        self
+    }
+
+    pub fn gamepad(mut self,bindings : GamepadBindings) -> Self {
+        self.config.gamepad = Some(bindings);
+        self
+    }
+
+    /// Only handle the events of the window and draw into its surface
+    pub fn window(mut self,window : EntityId) -> Self {
+        self.config.window = Some(window);
+        self
+    }
+
+    pub fn config(&self) -> &EguiConfig {
+        &self.config
+    }
+
+    pub fn build<S : Spawner>(self,spawner : &mut S,events : Events) -> Result<Prepared,Error> {
+        crate::build_with_config(spawner, events, self.config)
+    }
+}
@@ -0,0 +1,95 @@
+use std::cell::RefCell;
+use egui::CursorIcon;
+use sdl2_sys::{
+    SDL_CreateSystemCursor, SDL_Cursor, SDL_SetCursor, SDL_ShowCursor, SDL_SystemCursor, SDL_DISABLE, SDL_ENABLE
+};
+
+// None means hiding the cursor
+fn system_cursor(icon : CursorIcon) -> Option<SDL_SystemCursor> {
+    Some(match icon {
+        CursorIcon::None => return None,
+
+        CursorIcon::Default => SDL_SystemCursor::SDL_SYSTEM_CURSOR_ARROW,
+        CursorIcon::ContextMenu => SDL_SystemCursor::SDL_SYSTEM_CURSOR_ARROW,
+        CursorIcon::Help => SDL_SystemCursor::SDL_SYSTEM_CURSOR_ARROW,
+        CursorIcon::PointingHand => SDL_SystemCursor::SDL_SYSTEM_CURSOR_HAND,
+        CursorIcon::Progress => SDL_SystemCursor::SDL_SYSTEM_CURSOR_WAITARROW,
+        CursorIcon::Wait => SDL_SystemCursor::SDL_SYSTEM_CURSOR_WAIT,
+
+        CursorIcon::Cell => SDL_SystemCursor::SDL_SYSTEM_CURSOR_CROSSHAIR,
+        CursorIcon::Crosshair => SDL_SystemCursor::SDL_SYSTEM_CURSOR_CROSSHAIR,
+        CursorIcon::Text => SDL_SystemCursor::SDL_SYSTEM_CURSOR_IBEAM,
+        CursorIcon::VerticalText => SDL_SystemCursor::SDL_SYSTEM_CURSOR_IBEAM,
+
+        CursorIcon::Alias => SDL_SystemCursor::SDL_SYSTEM_CURSOR_ARROW,
+        CursorIcon::Copy => SDL_SystemCursor::SDL_SYSTEM_CURSOR_ARROW,
+        CursorIcon::Move => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZEALL,
+        CursorIcon::NoDrop => SDL_SystemCursor::SDL_SYSTEM_CURSOR_NO,
+        CursorIcon::NotAllowed => SDL_SystemCursor::SDL_SYSTEM_CURSOR_NO,
+        CursorIcon::Grab => SDL_SystemCursor::SDL_SYSTEM_CURSOR_HAND,
+        CursorIcon::Grabbing => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZEALL,
+
+        CursorIcon::AllScroll => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZEALL,
+        CursorIcon::ResizeHorizontal => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZEWE,
+        CursorIcon::ResizeNeSw => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZENESW,
+        CursorIcon::ResizeNwSe => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZENWSE,
+        CursorIcon::ResizeVertical => SDL_SystemCursor::SDL_SYSTEM_CURSOR_SIZENS,
+
+        CursorIcon::ZoomIn => SDL_SystemCursor::SDL_SYSTEM_CURSOR_CROSSHAIR,
+        CursorIcon::ZoomOut => SDL_SystemCursor::SDL_SYSTEM_CURSOR_CROSSHAIR,
+    })
+}
+
+// Cursors are created once and never freed.
+// elikar's `Mouse::set_cursor` takes the cursor by value and frees it when it drops,
+// leaving sdl with a dangling current cursor,so they are raw sdl cursors set with `SDL_SetCursor`.
+// Not a resource,cursors must stay in the main thread
+#[derive(Default)]
+struct Cursors {
+    // keyed by the system cursor,several icons share one.
+    // CursorIcon is not hashable and there are a few of them
+    cache : Vec<(SDL_SystemCursor,*mut SDL_Cursor)>,
+    current : Option<CursorIcon>,
+}
+
+thread_local! {
+    // the os cursor is shared by all the windows,so is `current`
+    static CURSORS : RefCell<Cursors> = RefCell::new(Cursors::default());
+}
+
+pub(crate) fn set(icon : CursorIcon) {
+    CURSORS.with(|cursors|cursors.borrow_mut().set(icon));
+}
+
+impl Cursors {
+    fn set(&mut self,icon : CursorIcon) {
+        if self.current == Some(icon) {
+            return;
+        }
+        self.current = Some(icon);
+
+        let system = match system_cursor(icon) {
+            Some(system) => system,
+            None => {
+                unsafe { SDL_ShowCursor(SDL_DISABLE as i32) };
+                return;
+            }
+        };
+        let cursor = match self.cache.iter().find(|(cached,_)|*cached == system) {
+            Some((_,cursor)) => *cursor,
+            None => {
+                let cursor = unsafe { SDL_CreateSystemCursor(system) };
+                // keep the current cursor
+                if cursor.is_null() {
+                    return;
+                }
+                self.cache.push((system,cursor));
+                cursor
+            }
+        };
+        unsafe {
+            SDL_SetCursor(cursor);
+            SDL_ShowCursor(SDL_ENABLE as i32);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use egui::{CtxRef, DroppedFile, HoveredFile, Pos2, RawInput, Rect};
+
+// a dropped file and the mouse position in window coordinates
+pub(crate) struct Drop {
+    pub(crate) path : PathBuf,
+    pub(crate) position : Option<Pos2>,
+}
+
+// Files dropped in this frame.
+// sdl reports nothing while files are dragged over the window and elikar only has the drops,
+// so the files are hovered in the frame they were dropped in only
+#[derive(Default)]
+pub(crate) struct Dropping {
+    files : Vec<HoveredFile>,
+}
+
+impl Dropping {
+    pub(crate) fn handle(&mut self,drop : Drop,raw_input : &mut RawInput,window_scale : f32) {
+        let Drop { path, position } = drop;
+        // move the pointer to the drop,`dropped_files` tests against it
+        if let Some(position) = position {
+            raw_input.events.push(egui::Event::PointerMoved(
+                (position.to_vec2() / window_scale).to_pos2()));
+        }
+        self.files.push(HoveredFile {
+            path: Some(path.clone()),
+            mime: String::new(),
+        });
+        let last_modified = std::fs::metadata(&path)
+            .and_then(|metadata|metadata.modified())
+            .ok();
+        raw_input.dropped_files.push(DroppedFile {
+            name: path.file_name()
+                .map(|name|name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: Some(path),
+            last_modified,
+            bytes: None,
+        });
+    }
+
+    // called every frame after handling the drops,
+    // egui keeps the hovered files until they are replaced
+    pub(crate) fn hover(&mut self,raw_input : &mut RawInput) {
+        raw_input.hovered_files = std::mem::take(&mut self.files);
+    }
+}
+
+/// Files dropped onto `rect` in this frame.
+/// sdl gives no position of a drop,the pointer is moved to the mouse when the files are dropped
+pub fn dropped_files(ctx : &CtxRef,rect : Rect) -> Vec<DroppedFile> {
+    let input = ctx.input();
+    match input.pointer.hover_pos() {
+        Some(pos) if rect.contains(pos) => input.raw.dropped_files.clone(),
+        _ => Vec::new()
+    }
+}
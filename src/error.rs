@@ -0,0 +1,60 @@
+use std::{any::type_name, fmt::Display};
+use xecs::world::World;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A resource egui needs was not registered in world,or attached to the window
+    MissingResource(&'static str),
+    /// There is no elikar window in world,or the chosen entity is not a window
+    NoWindow,
+    Surface(wgpu::SurfaceError),
+    Backend(egui_wgpu_backend::BackendError),
+    /// Failed to load a font file
+    Io(std::io::Error),
+    /// An sdl call elikar does not wrap failed,with the message of sdl
+    Sdl(String),
+    /// The pixels of a texture are empty or not as many as its size,`len` is in bytes
+    TextureSize {
+        size : (u32,u32),
+        len : usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::MissingResource(name) => write!(f,"Resource {} was not found in world",name),
+            Error::NoWindow => write!(f,"There is no window in world"),
+            Error::Surface(error) => write!(f,"Surface error : {}",error),
+            Error::Backend(error) => write!(f,"Render error : {}",error),
+            Error::Io(error) => write!(f,"IO error : {}",error),
+            Error::Sdl(error) => write!(f,"SDL error : {}",error),
+            Error::TextureSize { size, len } =>
+                write!(f,"Texture of {}x{} pixels can't be made from {} bytes",size.0,size.1,len),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(error : wgpu::SurfaceError) -> Self {
+        Error::Surface(error)
+    }
+}
+
+impl From<egui_wgpu_backend::BackendError> for Error {
+    fn from(error : egui_wgpu_backend::BackendError) -> Self {
+        Error::Backend(error)
+    }
+}
+
+pub(crate) fn missing<T>() -> Error {
+    Error::MissingResource(type_name::<T>())
+}
+
+pub(crate) fn require<T : Send + Sync + 'static>(world : &World) -> Result<(),Error> {
+    world.resource_read::<T>()
+        .map(|_|())
+        .ok_or_else(missing::<T>)
+}
@@ -1,10 +1,68 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
 use crossbeam::channel::{Receiver, unbounded};
 use egui::{Key, Modifiers};
 use futures::StreamExt;
-use elikar::{clipboard::Clipboard, common::Spawner, events::Events, keyboard::{Code, Keyboard}, mouse::events::button::Button};
-use xecs::system::System;
+use elikar::{clipboard::Clipboard, common::Spawner, events::Events, keyboard::{Code, Keyboard}, mouse::{Mouse, events::button::Button}, window::{Window, events::WindowEventType}};
+use xecs::{entity::EntityId, query::WithId, system::System};
+use sdl2_sys::{SDL_EventType, SDL_HINT_TOUCH_MOUSE_EVENTS, SDL_PRESSED, SDL_SetHint};
+use crate::{dnd::Drop, focus::WindowFocus, gamepad::{GamepadAxis, GamepadButton, Pad}, input::ExtraButton, repeat::DroppedRepeats, sdl::EventWatch, touch::Finger};
 
-fn key_map(code : Code) -> Option<Key> {
+// both sides of the modifier keys count,
+// `command` is cmd on mac and ctrl on the others
+macro_rules! modifiers {
+    ($kmod:expr) => {{
+        let kmod = $kmod;
+        Modifiers {
+            alt: kmod.alt(),
+            ctrl: kmod.ctrl(),
+            shift: kmod.shift(),
+            mac_cmd: cfg!(target_os = "macos") && kmod.gui(),
+            command: if cfg!(target_os = "macos") { kmod.gui() } else { kmod.ctrl() },
+        }
+    }};
+}
+
+enum Shortcut {
+    Copy,
+    Cut,
+    Paste,
+}
+
+fn shortcut(key : Key,modifiers : &Modifiers) -> Option<Shortcut> {
+    if !modifiers.command {
+        return None;
+    }
+    match key {
+        Key::C => Some(Shortcut::Copy),
+        Key::X => Some(Shortcut::Cut),
+        Key::V => Some(Shortcut::Paste),
+        _ => None
+    }
+}
+
+// events are sent in window coordinates,convert them to points before feeding egui
+pub fn to_points(event : egui::Event,window_scale : f32) -> egui::Event {
+    match event {
+        egui::Event::PointerMoved(pos) =>
+            egui::Event::PointerMoved(egui::pos2(
+                pos.x / window_scale,
+                pos.y / window_scale)),
+        egui::Event::PointerButton { pos, button, pressed, modifiers } =>
+            egui::Event::PointerButton {
+                pos: egui::pos2(
+                    pos.x / window_scale,
+                    pos.y / window_scale),
+                button,
+                pressed,
+                modifiers,
+            },
+        event => event
+    }
+}
+
+/// Map an elikar key code to egui key.
+/// egui has no keys for function keys,punctuations and keypad operators,they are `None`
+pub fn key_map(code : Code) -> Option<Key> {
     Some(match code {
         Code::Left => Key::ArrowLeft,
         Code::Up => Key::ArrowUp,
@@ -12,10 +70,10 @@ fn key_map(code : Code) -> Option<Key> {
         Code::Down => Key::ArrowDown,
 
         Code::Escape => Key::Escape,
-        Code::Tab => Key::Tab,
-        Code::Backspace => Key::Backspace,
-        Code::Space => Key::Space,
-        Code::Return => Key::Enter,
+        Code::Tab | Code::KpTab => Key::Tab,
+        Code::Backspace | Code::KpBackspace => Key::Backspace,
+        Code::Space | Code::KpSpace => Key::Space,
+        Code::Return | Code::KpEnter => Key::Enter,
 
         Code::Insert => Key::Insert,
         Code::Home => Key::Home,
@@ -66,28 +124,53 @@ fn key_map(code : Code) -> Option<Key> {
     })
 }
 
-pub fn keydown<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+// the os repeats the text input of a held key as well,
+// the ignored repeats are kept in `dropped_repeats` to drop their texts in the frame task
+pub fn keydown<S : Spawner>(
+    spawner : &mut S,
+    events : Events,
+    window : Option<EntityId>,
+    pass_repeats : bool,
+    dropped_repeats : Rc<RefCell<DroppedRepeats>>) -> Receiver<egui::Event> {
     let (tx,rx) = unbounded();
 
     spawner.spawn_local(async move {
         let mut on_key_down = events.on_key_down();
+        let world = on_key_down.world();
         let tx = tx;
         while let Some(key) = on_key_down.next().await {
+            if !in_window(window, key.window_id) {
+                continue;
+            }
+            if key.is_repeat && !pass_repeats {
+                dropped_repeats.borrow_mut().push(key.timestamp);
+                continue;
+            }
             let kmod = key.mod_state;
             if let Some(key) = key_map(key.code) {
+                let modifiers = modifiers!(kmod);
                 let event = egui::Event::Key{
                     key,
                     pressed: true,
-                    modifiers: Modifiers{
-                        alt: kmod.alt(),
-                        ctrl: kmod.ctrl(),
-                        shift: kmod.shift(),
-                        mac_cmd: kmod.gui(),
-                        command: 
-                            kmod.left_ctrl() || kmod.left_gui(),
+                    modifiers,
+                };
+                if tx.send(event).is_err() { break; }
+
+                let event = match shortcut(key, &modifiers) {
+                    Some(Shortcut::Copy) => Some(egui::Event::Copy),
+                    Some(Shortcut::Cut) => Some(egui::Event::Cut),
+                    Some(Shortcut::Paste) => {
+                        // nothing to paste if the clipboard can't be read
+                        let world = world.read();
+                        world.resource_read::<Clipboard>()
+                            .and_then(|clipboard|clipboard.get().ok())
+                            .map(egui::Event::Text)
                     },
+                    None => None
                 };
-                tx.send(event).unwrap();
+                if let Some(event) = event {
+                    if tx.send(event).is_err() { break; }
+                }
             }
         }
     });
@@ -95,45 +178,24 @@ pub fn keydown<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::
     rx
 }
 
-pub fn keyup<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+pub fn keyup<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<egui::Event> {
     let (tx,rx) = unbounded();
 
     spawner.spawn_local(async move {
         let mut on_key_up = events.on_key_up();
-        let world = on_key_up.world();
         let tx = tx;
         while let Some(key) = on_key_up.next().await {
+            if !in_window(window, key.window_id) {
+                continue;
+            }
             let kmod = key.mod_state;
             if let Some(key) = key_map(key.code) {
                 let event = egui::Event::Key{
                     key,
                     pressed: false,
-                    modifiers: Modifiers{
-                        alt: kmod.alt(),
-                        ctrl: kmod.ctrl(),
-                        shift: kmod.shift(),
-                        mac_cmd: kmod.gui(),
-                        command: 
-                            kmod.left_ctrl() || kmod.left_gui(),
-                    },
-                };
-                tx.send(event).unwrap();
-
-                let event = if key == Key::C && kmod.ctrl() {
-                    Some(egui::Event::Copy)
-                } else if key == Key::X && kmod.ctrl() {
-                    Some(egui::Event::Cut)
-                } else if key == Key::V && kmod.ctrl() {
-                    let world = world.read();
-                    let clipboard = world.resource_read::<Clipboard>().unwrap();
-                    let text = clipboard.get().unwrap();
-                    Some(egui::Event::Text(text))
-                } else {
-                    None
+                    modifiers: modifiers!(kmod),
                 };
-                if let Some(event) = event {
-                    tx.send(event).unwrap()
-                }
+                if tx.send(event).is_err() { break; }
             }
         }
     });
@@ -141,7 +203,7 @@ pub fn keyup<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Ev
     rx
 }
 
-pub fn mouse_down<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+pub fn mouse_down<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<egui::Event> {
     let (tx,rx) = unbounded();
 
     spawner.spawn_local(async move {
@@ -149,6 +211,9 @@ pub fn mouse_down<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egu
         let world = on_mouse_down.world();
         let tx = tx;
         while let Some(mouse) = on_mouse_down.next().await {
+            if !in_window(window, mouse.window_id) {
+                continue;
+            }
             let button = match mouse.button {
                 Button::Left => Some(egui::PointerButton::Primary),
                 Button::Middle => Some(egui::PointerButton::Middle),
@@ -158,8 +223,11 @@ pub fn mouse_down<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egu
             if let Some(button) = button {
                 let kmod = {
                     let world = world.read();
-                    let keyboard = world.resource_read::<Keyboard>().unwrap();
-                    keyboard.mod_state()
+                    let keyboard = world.resource_read::<Keyboard>();
+                    match keyboard {
+                        Some(keyboard) => keyboard.mod_state(),
+                        None => continue
+                    }
                 };
                 let event = egui::Event::PointerButton {
                     pos: egui::Pos2 { 
@@ -168,16 +236,9 @@ pub fn mouse_down<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egu
                     },
                     button,
                     pressed: true,
-                    modifiers: Modifiers {
-                        alt: kmod.alt(),
-                        ctrl: kmod.ctrl(),
-                        shift: kmod.shift(),
-                        mac_cmd: kmod.gui(),
-                        command: 
-                            kmod.left_ctrl() || kmod.left_gui(),
-                    },
+                    modifiers: modifiers!(kmod),
                 };
-                tx.send(event).unwrap();
+                if tx.send(event).is_err() { break; }
             }
         }
     });
@@ -185,7 +246,7 @@ pub fn mouse_down<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egu
     rx
 }
 
-pub fn mouse_up<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+pub fn mouse_up<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<egui::Event> {
     let (tx,rx) = unbounded();
 
     spawner.spawn_local(async move {
@@ -193,6 +254,9 @@ pub fn mouse_up<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui:
         let world = on_mouse_up.world();
         let tx = tx;
         while let Some(mouse) = on_mouse_up.next().await {
+            if !in_window(window, mouse.window_id) {
+                continue;
+            }
             let button = match mouse.button {
                 Button::Left => Some(egui::PointerButton::Primary),
                 Button::Middle => Some(egui::PointerButton::Middle),
@@ -202,8 +266,11 @@ pub fn mouse_up<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui:
             if let Some(button) = button {
                 let kmod = {
                     let world = world.read();
-                    let keyboard = world.resource_read::<Keyboard>().unwrap();
-                    keyboard.mod_state()
+                    let keyboard = world.resource_read::<Keyboard>();
+                    match keyboard {
+                        Some(keyboard) => keyboard.mod_state(),
+                        None => continue
+                    }
                 };
                 let event = egui::Event::PointerButton {
                     pos: egui::Pos2 { 
@@ -212,16 +279,9 @@ pub fn mouse_up<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui:
                     },
                     button,
                     pressed: false,
-                    modifiers: Modifiers {
-                        alt: kmod.alt(),
-                        ctrl: kmod.ctrl(),
-                        shift: kmod.shift(),
-                        mac_cmd: kmod.gui(),
-                        command: 
-                            kmod.left_ctrl() || kmod.left_gui(),
-                    },
+                    modifiers: modifiers!(kmod),
                 };
-                tx.send(event).unwrap();
+                if tx.send(event).is_err() { break; }
             }
         }
     });
@@ -229,25 +289,38 @@ pub fn mouse_up<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui:
     rx
 }
 
-pub fn mouse_motion<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+pub fn mouse_motion<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<egui::Event> {
     let (tx,rx) = unbounded();
 
     spawner.spawn_local(async move {
         let mut on_mouse_motion = events.on_mouse_motion();
         let tx = tx;
         while let Some(mouse) = on_mouse_motion.next().await {
+            if !in_window(window, mouse.window_id) {
+                continue;
+            }
             let event = egui::Event::PointerMoved(egui::Pos2{
                 x: mouse.position.0 as f32,
                 y: mouse.position.1 as f32,
             });
-            tx.send(event).unwrap();
+            if tx.send(event).is_err() { break; }
         }
     });
 
     rx
 }
 
-pub fn mouse_wheel<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+// events of other windows are dropped if egui was built for a window
+fn in_window(window : Option<EntityId>,window_id : EntityId) -> bool {
+    window.is_none_or(|window|window == window_id)
+}
+
+// the same zoom speed as scrolling 8 points per tick
+const ZOOM_PER_TICK : f32 = 8.0 / 125.0;
+
+// scroll events are sent in wheel ticks,
+// the frame task scales them by `WheelScroll`
+pub fn mouse_wheel<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<egui::Event> {
     let (tx,rx) = unbounded();
 
     spawner.spawn_local(async move {
@@ -255,53 +328,384 @@ pub fn mouse_wheel<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<eg
         let world = on_mouse_wheel.world();
         let tx = tx;
         while let Some(wheel) = on_mouse_wheel.next().await {
-            let delta = egui::vec2(
-                wheel.scrolled.0 as f32 * 8.0,
-                wheel.scrolled.1 as f32 * 8.0
-            );
+            if !in_window(window, wheel.window_id) {
+                continue;
+            }
+            // elikar only has whole ticks,smooth scrolling touchpads are rounded by sdl
+            // sdl scrolls right with positive x,egui moves the content right with it
+            let ticks = egui::vec2(-wheel.scrolled.0 as f32, wheel.scrolled.1 as f32);
             let kmod = {
                 let world = world.read();
-                let keyboard = world.resource_read::<Keyboard>().unwrap();
-                keyboard.mod_state()
+                let keyboard = world.resource_read::<Keyboard>();
+                match keyboard {
+                    Some(keyboard) => keyboard.mod_state(),
+                    None => continue
+                }
             };
             let event = if kmod.ctrl() {
-                egui::Event::Zoom((delta.y / 125.0).exp())
+                egui::Event::Zoom((ticks.y * ZOOM_PER_TICK).exp())
+            } else if kmod.shift() && ticks.x == 0.0 {
+                // shift scrolls horizontally
+                egui::Event::Scroll(egui::vec2(ticks.y, 0.0))
             } else {
-                egui::Event::Scroll(delta)
+                egui::Event::Scroll(ticks)
             };
-            tx.send(event).unwrap();
+            if tx.send(event).is_err() { break; }
         }
     });
 
     rx
 }
 
-pub fn text_input<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+pub fn scale_scroll(event : egui::Event,points_per_tick : egui::Vec2) -> egui::Event {
+    match event {
+        egui::Event::Scroll(ticks) => egui::Event::Scroll(ticks * points_per_tick),
+        event => event
+    }
+}
+
+// X1 and X2 buttons,egui has no pointer buttons for them
+pub fn extra_buttons<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<(ExtraButton,bool)> {
     let (tx,rx) = unbounded();
 
+    let events_ = events.clone();
+    let tx_ = tx.clone();
     spawner.spawn_local(async move {
-        let mut text_input = events.on_text_input();
+        let mut on_mouse_down = events_.on_mouse_down();
+        let tx = tx_;
+        while let Some(mouse) = on_mouse_down.next().await {
+            if !in_window(window, mouse.window_id) {
+                continue;
+            }
+            if let Some(button) = extra_button(mouse.button) {
+                if tx.send((button,true)).is_err() { break; }
+            }
+        }
+    });
+
+    spawner.spawn_local(async move {
+        let mut on_mouse_up = events.on_mouse_up();
         let tx = tx;
-        while let Some(input) = text_input.next().await {
-            let event = egui::Event::Text(input.text);
-            tx.send(event).unwrap();
+        while let Some(mouse) = on_mouse_up.next().await {
+            if !in_window(window, mouse.window_id) {
+                continue;
+            }
+            if let Some(button) = extra_button(mouse.button) {
+                if tx.send((button,false)).is_err() { break; }
+            }
         }
     });
 
     rx
 }
 
-pub fn text_editing<S : Spawner>(spawner : &mut S,events : Events) -> Receiver<egui::Event> {
+fn extra_button(button : Button) -> Option<ExtraButton> {
+    match button {
+        Button::X1 => Some(ExtraButton::X1),
+        Button::X2 => Some(ExtraButton::X2),
+        _ => None
+    }
+}
+
+// text input and ime composition share one channel to keep their order
+// the events come with the sdl timestamp of their input
+pub fn text_input<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<(u32,egui::Event)> {
     let (tx,rx) = unbounded();
+    // both tasks run in the main thread
+    let composing = Rc::new(Cell::new(false));
+
+    let events_ = events.clone();
+    let tx_ = tx.clone();
+    let composing_ = composing.clone();
+    spawner.spawn_local(async move {
+        let mut text_input = events_.on_text_input();
+        let tx = tx_;
+        while let Some(input) = text_input.next().await {
+            if !in_window(window, input.window_id) {
+                continue;
+            }
+            // the ime committed the composition
+            let event = if composing_.replace(false) {
+                egui::Event::CompositionEnd(input.text)
+            } else {
+                egui::Event::Text(input.text)
+            };
+            if tx.send((input.timestamp,event)).is_err() { break; }
+        }
+    });
 
     spawner.spawn_local(async move {
         let mut edit = events.on_text_editing();
         let tx = tx;
         while let Some(edit) = edit.next().await {
-            let event = egui::Event::CompositionUpdate(edit.text);
-            tx.send(event).unwrap();
+            if !in_window(window, edit.window_id) {
+                continue;
+            }
+            let composition = match (composing.get(),edit.text.is_empty()) {
+                (false,true) => continue,
+                (false,false) => {
+                    composing.set(true);
+                    vec![
+                        egui::Event::CompositionStart,
+                        egui::Event::CompositionUpdate(edit.text)
+                    ]
+                },
+                // the composition was canceled
+                (true,true) => {
+                    composing.set(false);
+                    vec![egui::Event::CompositionEnd(String::new())]
+                },
+                (true,false) => vec![egui::Event::CompositionUpdate(edit.text)],
+            };
+            if composition.into_iter().any(|event|tx.send((edit.timestamp,event)).is_err()) { break; }
+        }
+    });
+
+    rx
+}
+
+pub fn window_resized<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<(u32,u32)> {
+    let (tx,rx) = unbounded();
+
+    spawner.spawn_local(async move {
+        let mut window_event = events.on_window_events();
+        let tx = tx;
+        while let Some(event) = window_event.next().await {
+            if !in_window(window, event.window_id) {
+                continue;
+            }
+            let size = match event.event_type {
+                WindowEventType::Resized(w, h) => Some((w,h)),
+                WindowEventType::SizeChanged(w, h) => Some((w,h)),
+                _ => None
+            };
+            if let Some((w,h)) = size {
+                // minimized
+                if w == 0 || h == 0 { continue; }
+                if tx.send((w as _,h as _)).is_err() { break; }
+            }
         }
     });
 
     rx
 }
+
+pub fn window_focus<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<WindowFocus> {
+    let (tx,rx) = unbounded();
+
+    spawner.spawn_local(async move {
+        let mut window_event = events.on_window_events();
+        let tx = tx;
+        while let Some(event) = window_event.next().await {
+            if !in_window(window, event.window_id) {
+                continue;
+            }
+            let focus = match event.event_type {
+                WindowEventType::Enter => WindowFocus::Enter,
+                WindowEventType::Leave => WindowFocus::Leave,
+                WindowEventType::FocusGained => WindowFocus::Gained,
+                WindowEventType::FocusLost => WindowFocus::Lost,
+                _ => continue
+            };
+            if tx.send(focus).is_err() { break; }
+        }
+    });
+
+    rx
+}
+
+pub fn drop_files<S : Spawner>(spawner : &mut S,events : Events,window : Option<EntityId>) -> Receiver<Drop> {
+    let (tx,rx) = unbounded();
+
+    spawner.spawn_local(async move {
+        let mut on_drop_file = events.on_drop_file();
+        let world = on_drop_file.world();
+        let tx = tx;
+        while let Some(drop) = on_drop_file.next().await {
+            if !in_window(window, drop.window_id) {
+                continue;
+            }
+            // sdl sends no motion while dragging files from the os,
+            // read where the mouse is relative to the window
+            let position = {
+                let world = world.read();
+                let mouse = world.resource_read::<Mouse>();
+                let position = world.query::<&Window>()
+                    .with_id()
+                    .find(|(id,_)|*id == drop.window_id)
+                    .zip(mouse)
+                    .map(|((_,window),mouse)|{
+                        let (x,y) = mouse.global_position();
+                        let (window_x,window_y) = window.position();
+                        egui::pos2((x - window_x as i32) as f32, (y - window_y as i32) as f32)
+                    });
+                position
+            };
+            let drop = Drop {
+                path: drop.path,
+                position,
+            };
+            if tx.send(drop).is_err() { break; }
+        }
+    });
+
+    rx
+}
+
+// elikar dispatches no finger events,they are watched on sdl.
+// sdl emulates the mouse with touch by default,it is turned off to take the fingers only
+pub fn touch() -> (EventWatch,Receiver<Finger>) {
+    let (tx,rx) = unbounded();
+    unsafe {
+        SDL_SetHint(SDL_HINT_TOUCH_MOUSE_EVENTS.as_ptr() as _, c"0".as_ptr());
+    }
+
+    let watch = EventWatch::new(move |event|{
+        let event_type = unsafe { event.type_ };
+        let phase = if event_type == SDL_EventType::SDL_FINGERDOWN as u32 {
+            egui::TouchPhase::Start
+        } else if event_type == SDL_EventType::SDL_FINGERMOTION as u32 {
+            egui::TouchPhase::Move
+        } else if event_type == SDL_EventType::SDL_FINGERUP as u32 {
+            egui::TouchPhase::End
+        } else {
+            return;
+        };
+        let finger = unsafe { event.tfinger };
+        let _ = tx.send(Finger {
+            device: finger.touchId as _,
+            id: finger.fingerId as _,
+            phase,
+            position: (finger.x,finger.y),
+            force: finger.pressure,
+        });
+    });
+
+    (watch,rx)
+}
+
+// elikar dispatches no controller events,they are watched on sdl like touch
+pub fn gamepad() -> (EventWatch,Receiver<Pad>) {
+    let (tx,rx) = unbounded();
+
+    let watch = EventWatch::new(move |event|{
+        let event_type = unsafe { event.type_ };
+        let pad = if event_type == SDL_EventType::SDL_CONTROLLERBUTTONDOWN as u32
+            || event_type == SDL_EventType::SDL_CONTROLLERBUTTONUP as u32 {
+            let button = unsafe { event.cbutton };
+            match GamepadButton::from_sdl(button.button) {
+                Some(gamepad_button) => Pad::Button(gamepad_button, button.state == SDL_PRESSED as u8),
+                None => return
+            }
+        } else if event_type == SDL_EventType::SDL_CONTROLLERAXISMOTION as u32 {
+            let axis = unsafe { event.caxis };
+            match GamepadAxis::from_sdl(axis.axis) {
+                Some(gamepad_axis) => Pad::Axis(gamepad_axis, axis.value),
+                None => return
+            }
+        } else if event_type == SDL_EventType::SDL_CONTROLLERDEVICEADDED as u32 {
+            Pad::Added(unsafe { event.cdevice.which })
+        } else if event_type == SDL_EventType::SDL_CONTROLLERDEVICEREMOVED as u32 {
+            Pad::Removed(unsafe { event.cdevice.which })
+        } else {
+            return;
+        };
+        let _ = tx.send(pad);
+    });
+
+    (watch,rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Key;
+    use elikar::keyboard::Code;
+    use super::key_map;
+
+    // every egui key,the keypad aliases and a few unmapped codes
+    const TABLE : &[(Code,Option<Key>)] = &[
+        (Code::Left, Some(Key::ArrowLeft)),
+        (Code::Up, Some(Key::ArrowUp)),
+        (Code::Right, Some(Key::ArrowRight)),
+        (Code::Down, Some(Key::ArrowDown)),
+
+        (Code::Escape, Some(Key::Escape)),
+        (Code::Tab, Some(Key::Tab)),
+        (Code::KpTab, Some(Key::Tab)),
+        (Code::Backspace, Some(Key::Backspace)),
+        (Code::KpBackspace, Some(Key::Backspace)),
+        (Code::Space, Some(Key::Space)),
+        (Code::KpSpace, Some(Key::Space)),
+        (Code::Return, Some(Key::Enter)),
+        (Code::KpEnter, Some(Key::Enter)),
+
+        (Code::Insert, Some(Key::Insert)),
+        (Code::Home, Some(Key::Home)),
+        (Code::Delete, Some(Key::Delete)),
+        (Code::End, Some(Key::End)),
+        (Code::Pagedown, Some(Key::PageDown)),
+        (Code::Pageup, Some(Key::PageUp)),
+
+        (Code::_0, Some(Key::Num0)),
+        (Code::_1, Some(Key::Num1)),
+        (Code::_2, Some(Key::Num2)),
+        (Code::_3, Some(Key::Num3)),
+        (Code::_4, Some(Key::Num4)),
+        (Code::_5, Some(Key::Num5)),
+        (Code::_6, Some(Key::Num6)),
+        (Code::_7, Some(Key::Num7)),
+        (Code::_8, Some(Key::Num8)),
+        (Code::_9, Some(Key::Num9)),
+        (Code::Kp0, Some(Key::Num0)),
+        (Code::Kp1, Some(Key::Num1)),
+        (Code::Kp2, Some(Key::Num2)),
+        (Code::Kp3, Some(Key::Num3)),
+        (Code::Kp4, Some(Key::Num4)),
+        (Code::Kp5, Some(Key::Num5)),
+        (Code::Kp6, Some(Key::Num6)),
+        (Code::Kp7, Some(Key::Num7)),
+        (Code::Kp8, Some(Key::Num8)),
+        (Code::Kp9, Some(Key::Num9)),
+
+        (Code::A, Some(Key::A)),
+        (Code::B, Some(Key::B)),
+        (Code::C, Some(Key::C)),
+        (Code::D, Some(Key::D)),
+        (Code::E, Some(Key::E)),
+        (Code::F, Some(Key::F)),
+        (Code::G, Some(Key::G)),
+        (Code::H, Some(Key::H)),
+        (Code::I, Some(Key::I)),
+        (Code::J, Some(Key::J)),
+        (Code::K, Some(Key::K)),
+        (Code::L, Some(Key::L)),
+        (Code::M, Some(Key::M)),
+        (Code::N, Some(Key::N)),
+        (Code::O, Some(Key::O)),
+        (Code::P, Some(Key::P)),
+        (Code::Q, Some(Key::Q)),
+        (Code::R, Some(Key::R)),
+        (Code::S, Some(Key::S)),
+        (Code::T, Some(Key::T)),
+        (Code::U, Some(Key::U)),
+        (Code::V, Some(Key::V)),
+        (Code::W, Some(Key::W)),
+        (Code::X, Some(Key::X)),
+        (Code::Y, Some(Key::Y)),
+        (Code::Z, Some(Key::Z)),
+
+        // egui 0.16 has no keys for them
+        (Code::F1, None),
+        (Code::F12, None),
+        (Code::Minus, None),
+        (Code::KpPlus, None),
+        (Code::Lctrl, None),
+    ];
+
+    #[test]
+    fn key_map_table() {
+        for (code,key) in TABLE.iter().copied() {
+            assert_eq!(key_map(code), key, "{:?}", code);
+        }
+    }
+}
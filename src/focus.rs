@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+use egui::{Key, Modifiers, PointerButton, Pos2};
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub(crate) enum WindowFocus {
+    /// The mouse entered the window
+    Enter,
+    /// The mouse left the window
+    Leave,
+    Gained,
+    Lost,
+}
+
+// Keys and buttons egui believes to be held
+#[derive(Default)]
+pub(crate) struct Held {
+    keys : HashSet<Key>,
+    buttons : Vec<PointerButton>,
+    pointer : Option<Pos2>,
+}
+
+impl Held {
+    // look at the events sent to egui
+    pub(crate) fn observe(&mut self,event : &egui::Event) {
+        match *event {
+            egui::Event::Key { key, pressed: true, .. } => { self.keys.insert(key); },
+            egui::Event::Key { key, pressed: false, .. } => { self.keys.remove(&key); },
+            egui::Event::PointerMoved(pos) => self.pointer = Some(pos),
+            egui::Event::PointerButton { pos, button, pressed, .. } => {
+                self.pointer = Some(pos);
+                self.buttons.retain(|held|*held != button);
+                if pressed {
+                    self.buttons.push(button);
+                }
+            },
+            egui::Event::PointerGone => self.pointer = None,
+            _ => {}
+        }
+    }
+
+    pub(crate) fn is_dragging(&self) -> bool {
+        !self.buttons.is_empty()
+    }
+
+    // the window lost focus,releases never come
+    pub(crate) fn release_all(&mut self) -> Vec<egui::Event> {
+        let mut events : Vec<_> = self.keys.drain()
+            .map(|key|egui::Event::Key {
+                key,
+                pressed: false,
+                modifiers: Modifiers::default(),
+            })
+            .collect();
+        if let Some(pos) = self.pointer {
+            events.extend(self.buttons.drain(..)
+                .map(|button|egui::Event::PointerButton {
+                    pos,
+                    button,
+                    pressed: false,
+                    modifiers: Modifiers::default(),
+                }));
+        }
+        self.buttons.clear();
+        events
+    }
+}
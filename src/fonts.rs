@@ -0,0 +1,45 @@
+use std::{io, path::Path};
+use egui::{FontData, FontDefinitions, FontFamily};
+
+/// Read a ttf/otf font file at runtime
+pub fn load<P : AsRef<Path>>(path : P) -> io::Result<FontData> {
+    Ok(FontData::from_owned(std::fs::read(path)?))
+}
+
+/// Use the font before all the others of proportional family,
+/// and as the last fallback of monospace family
+pub fn insert_primary(fonts : &mut FontDefinitions,name : &str,data : FontData) {
+    fonts.font_data.insert(name.to_owned(), data);
+    for (family,names) in fonts.fonts_for_family.iter_mut() {
+        match family {
+            FontFamily::Proportional => names.insert(0, name.to_owned()),
+            _ => names.push(name.to_owned()),
+        }
+    }
+}
+
+/// Use the font when the glyph was not found in all the other fonts
+pub fn append_fallback(fonts : &mut FontDefinitions,name : &str,data : FontData) {
+    fonts.font_data.insert(name.to_owned(), data);
+    for names in fonts.fonts_for_family.values_mut() {
+        names.push(name.to_owned());
+    }
+}
+
+/// The CJK font bundled at build time.
+/// simfang can't be redistributed,so the crate ships no font file,
+/// `ELIKAR_EGUI_CJK_FONT` names one,e.g. an absolute path to NotoSansSC-Regular.otf
+#[cfg(feature = "cjk_font")]
+pub fn cjk_font() -> FontData {
+    FontData::from_static(include_bytes!(env!(
+        "ELIKAR_EGUI_CJK_FONT",
+        "cjk_font needs ELIKAR_EGUI_CJK_FONT set to the absolute path of a CJK font file")))
+}
+
+pub(crate) fn default_fonts() -> FontDefinitions {
+    #[allow(unused_mut)]
+    let mut fonts = FontDefinitions::default();
+    #[cfg(feature = "cjk_font")]
+    insert_primary(&mut fonts, "cjk", cjk_font());
+    fonts
+}
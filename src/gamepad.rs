@@ -0,0 +1,293 @@
+use std::ffi::CStr;
+use egui::{Key, Modifiers, Stroke};
+use sdl2_sys::{
+    SDL_GameController, SDL_GameControllerClose, SDL_GameControllerGetJoystick, SDL_GameControllerOpen,
+    SDL_GetError, SDL_INIT_GAMECONTROLLER, SDL_InitSubSystem, SDL_IsGameController, SDL_JoystickInstanceID,
+    SDL_NumJoysticks, SDL_QuitSubSystem, SDL_bool
+};
+use crate::Error;
+
+/// Buttons of a controller,laid out like an xbox controller
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    // SDL_GameControllerButton,the newer buttons are not bound
+    pub(crate) fn from_sdl(button : u8) -> Option<Self> {
+        Some(match button {
+            0 => GamepadButton::A,
+            1 => GamepadButton::B,
+            2 => GamepadButton::X,
+            3 => GamepadButton::Y,
+            4 => GamepadButton::Back,
+            5 => GamepadButton::Guide,
+            6 => GamepadButton::Start,
+            7 => GamepadButton::LeftStick,
+            8 => GamepadButton::RightStick,
+            9 => GamepadButton::LeftShoulder,
+            10 => GamepadButton::RightShoulder,
+            11 => GamepadButton::DPadUp,
+            12 => GamepadButton::DPadDown,
+            13 => GamepadButton::DPadLeft,
+            14 => GamepadButton::DPadRight,
+            _ => return None
+        })
+    }
+}
+
+/// Axes of a controller,y points down
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    /// from 0 to the max only
+    TriggerLeft,
+    /// from 0 to the max only
+    TriggerRight,
+}
+
+impl GamepadAxis {
+    // SDL_GameControllerAxis
+    pub(crate) fn from_sdl(axis : u8) -> Option<Self> {
+        Some(match axis {
+            0 => GamepadAxis::LeftX,
+            1 => GamepadAxis::LeftY,
+            2 => GamepadAxis::RightX,
+            3 => GamepadAxis::RightY,
+            4 => GamepadAxis::TriggerLeft,
+            5 => GamepadAxis::TriggerRight,
+            _ => return None
+        })
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum NavAction {
+    /// Tab
+    Next,
+    /// Shift-Tab
+    Previous,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Enter on the focused widget
+    Activate,
+    /// Escape
+    Cancel,
+}
+
+impl NavAction {
+    fn key(self) -> (Key,Modifiers) {
+        match self {
+            NavAction::Next => (Key::Tab,Modifiers::default()),
+            NavAction::Previous => (Key::Tab,Modifiers { shift: true, ..Default::default() }),
+            NavAction::Up => (Key::ArrowUp,Modifiers::default()),
+            NavAction::Down => (Key::ArrowDown,Modifiers::default()),
+            NavAction::Left => (Key::ArrowLeft,Modifiers::default()),
+            NavAction::Right => (Key::ArrowRight,Modifiers::default()),
+            NavAction::Activate => (Key::Enter,Modifiers::default()),
+            NavAction::Cancel => (Key::Escape,Modifiers::default()),
+        }
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct GamepadBindings {
+    pub buttons : Vec<(GamepadButton,NavAction)>,
+    /// actions of the negative and the positive direction of an axis
+    pub axes : Vec<(GamepadAxis,NavAction,NavAction)>,
+    /// how far an axis must be pushed to act,from 0 to 1
+    pub threshold : f32,
+    /// drawn around the focused widget,`None` keeps the style
+    pub focus_highlight : Option<Stroke>,
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        GamepadBindings {
+            buttons: vec![
+                (GamepadButton::DPadUp,NavAction::Previous),
+                (GamepadButton::DPadDown,NavAction::Next),
+                (GamepadButton::DPadLeft,NavAction::Left),
+                (GamepadButton::DPadRight,NavAction::Right),
+                (GamepadButton::A,NavAction::Activate),
+                (GamepadButton::B,NavAction::Cancel),
+            ],
+            axes: vec![
+                (GamepadAxis::LeftY,NavAction::Previous,NavAction::Next),
+                (GamepadAxis::LeftX,NavAction::Left,NavAction::Right),
+            ],
+            threshold: 0.5,
+            focus_highlight: Some(Stroke::new(2.0, egui::Color32::from_rgb(255,200,0))),
+        }
+    }
+}
+
+pub(crate) enum Pad {
+    Button(GamepadButton,bool),
+    Axis(GamepadAxis,i16),
+    // the joystick index of a plugged controller
+    Added(i32),
+    // the instance id of an unplugged controller
+    Removed(i32),
+}
+
+// Controllers send no events until they are opened,
+// elikar does not init the controller subsystem.
+// Closed when dropped,kept by the frame task
+pub(crate) struct Controllers {
+    opened : Vec<*mut SDL_GameController>,
+}
+
+impl Controllers {
+    pub(crate) fn open() -> Result<Self,Error> {
+        if unsafe { SDL_InitSubSystem(SDL_INIT_GAMECONTROLLER) } != 0 {
+            let error = unsafe { CStr::from_ptr(SDL_GetError()) };
+            return Err(Error::Sdl(error.to_string_lossy().into_owned()));
+        }
+        let mut controllers = Controllers { opened: Vec::new() };
+        // the plugged ones may send no device events
+        for index in 0..unsafe { SDL_NumJoysticks() } {
+            controllers.add(index);
+        }
+        Ok(controllers)
+    }
+
+    fn instance_id(controller : *mut SDL_GameController) -> i32 {
+        unsafe { SDL_JoystickInstanceID(SDL_GameControllerGetJoystick(controller)) }
+    }
+
+    // an already opened controller is opened again and closed once,
+    // sdl counts the references
+    fn add(&mut self,index : i32) {
+        if unsafe { SDL_IsGameController(index) } == SDL_bool::SDL_FALSE {
+            return;
+        }
+        let controller = unsafe { SDL_GameControllerOpen(index) };
+        if controller.is_null() {
+            return;
+        }
+        let id = Self::instance_id(controller);
+        if self.opened.iter().any(|opened|Self::instance_id(*opened) == id) {
+            unsafe { SDL_GameControllerClose(controller) };
+        } else {
+            self.opened.push(controller);
+        }
+    }
+
+    pub(crate) fn handle(&mut self,pad : &Pad) {
+        match *pad {
+            Pad::Added(index) => self.add(index),
+            Pad::Removed(id) => self.opened.retain(|opened|{
+                let removed = Self::instance_id(*opened) == id;
+                if removed {
+                    unsafe { SDL_GameControllerClose(*opened) };
+                }
+                !removed
+            }),
+            _ => {}
+        }
+    }
+}
+
+impl Drop for Controllers {
+    fn drop(&mut self) {
+        for controller in self.opened.drain(..) {
+            unsafe { SDL_GameControllerClose(controller) };
+        }
+        unsafe { SDL_QuitSubSystem(SDL_INIT_GAMECONTROLLER) };
+    }
+}
+
+// Turns controller events into keyboard navigation
+pub(crate) struct Navigator {
+    bindings : GamepadBindings,
+    // the action each axis is holding
+    held_axes : Vec<(GamepadAxis,NavAction)>,
+}
+
+impl Navigator {
+    pub(crate) fn new(bindings : GamepadBindings) -> Self {
+        Navigator {
+            bindings,
+            held_axes: Vec::new(),
+        }
+    }
+
+    fn key_event(action : NavAction,pressed : bool) -> egui::Event {
+        let (key,modifiers) = action.key();
+        egui::Event::Key { key, pressed, modifiers }
+    }
+
+    pub(crate) fn handle(&mut self,pad : Pad) -> Vec<egui::Event> {
+        match pad {
+            Pad::Button(button,pressed) => self.bindings.buttons.iter()
+                .filter(|(bound,_)|*bound == button)
+                .map(|(_,action)|Self::key_event(*action, pressed))
+                .collect(),
+            Pad::Axis(axis,value) => {
+                let value = value as f32 / i16::MAX as f32;
+                let action = self.bindings.axes.iter()
+                    .find(|(bound,_,_)|*bound == axis)
+                    .and_then(|(_,negative,positive)| {
+                        if value <= -self.bindings.threshold {
+                            Some(*negative)
+                        } else if value >= self.bindings.threshold {
+                            Some(*positive)
+                        } else {
+                            None
+                        }
+                    });
+                let held = self.held_axes.iter()
+                    .position(|(held,_)|*held == axis);
+                let mut events = Vec::new();
+                // only crossing the threshold acts,like pressing a button
+                match (held.map(|index|self.held_axes[index].1),action) {
+                    (Some(old),Some(new)) if old == new => {},
+                    (old,new) => {
+                        if let Some(old) = old {
+                            events.push(Self::key_event(old, false));
+                        }
+                        self.held_axes.retain(|(held,_)|*held != axis);
+                        if let Some(new) = new {
+                            events.push(Self::key_event(new, true));
+                            self.held_axes.push((axis,new));
+                        }
+                    }
+                }
+                events
+            },
+            Pad::Added(_) | Pad::Removed(_) => Vec::new(),
+        }
+    }
+
+    // the focused widget uses active visuals
+    pub(crate) fn highlight(&self,ctx : &egui::CtxRef) {
+        if let Some(stroke) = self.bindings.focus_highlight {
+            if ctx.style().visuals.widgets.active.bg_stroke != stroke {
+                let mut style = (*ctx.style()).clone();
+                style.visuals.widgets.active.bg_stroke = stroke;
+                ctx.set_style(style);
+            }
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use std::{collections::HashMap, fs::File, io::{self, BufReader, BufWriter}, path::Path};
+use egui::{ClippedMesh, Color32, CtxRef, FontImage, Pos2, RawInput, Rect, Rgba, TextureId, epaint::Vertex};
+use crate::fonts;
+
+/// 8 bit sRGBA pixels,not premultiplied,rows are tightly packed
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct RgbaImage {
+    pub width : usize,
+    pub height : usize,
+    pub pixels : Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width : usize,height : usize,color : Color32) -> Self {
+        let pixels = Rgba::from(color).to_srgba_unmultiplied();
+        RgbaImage {
+            width,
+            height,
+            pixels: pixels.iter().copied().cycle().take(width * height * 4).collect(),
+        }
+    }
+
+    pub fn get(&self,x : usize,y : usize) -> Color32 {
+        let i = (y * self.width + x) * 4;
+        let p = &self.pixels[i..i + 4];
+        Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3])
+    }
+
+    /// Count the pixels with a channel differing by more than `tolerance`,
+    /// e.g. against a golden image
+    pub fn diff(&self,other : &RgbaImage,tolerance : u8) -> usize {
+        if self.width != other.width || self.height != other.height {
+            return self.width.max(other.width) * self.height.max(other.height);
+        }
+        self.pixels.chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+            .filter(|(a,b)|a.iter().zip(b.iter()).any(|(a,b)|a.abs_diff(*b) > tolerance))
+            .count()
+    }
+
+    pub fn save_png<P : AsRef<Path>>(&self,path : P) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()
+            .and_then(|mut writer|writer.write_image_data(&self.pixels))
+            .map_err(io::Error::other)
+    }
+
+    /// Load an 8 bit rgb or rgba png
+    pub fn load_png<P : AsRef<Path>>(path : P) -> io::Result<Self> {
+        let invalid = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        // palettes and low bit depths become 8 bit rgb(a)
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let mut reader = decoder.read_info().map_err(invalid)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(invalid)?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match (info.color_type,info.bit_depth) {
+            (png::ColorType::Rgba,png::BitDepth::Eight) => buffer,
+            (png::ColorType::Rgb,png::BitDepth::Eight) => buffer.chunks_exact(3)
+                .flat_map(|p|[p[0], p[1], p[2], 255])
+                .collect(),
+            (color_type,bit_depth) => return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported png format {:?} {:?}", color_type, bit_depth)))
+        };
+        Ok(RgbaImage {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+}
+
+/// egui without a gpu,frames are rasterized on the cpu.
+/// For tests and screenshots,it needs no window,device or events
+pub struct EguiHeadless {
+    ctx : CtxRef,
+    raw_input : RawInput,
+    size : (u32,u32),
+    pixels_per_point : f32,
+    clear_color : Color32,
+    textures : HashMap<u64,RgbaImage>,
+    next_texture : u64,
+}
+
+impl EguiHeadless {
+    /// `size` is in physical pixels
+    pub fn new(size : (u32,u32),pixels_per_point : f32) -> Self {
+        let ctx = CtxRef::default();
+        ctx.set_fonts(fonts::default_fonts());
+        EguiHeadless {
+            ctx,
+            raw_input: RawInput::default(),
+            size,
+            pixels_per_point,
+            clear_color: Color32::WHITE,
+            textures: HashMap::new(),
+            next_texture: 0,
+        }
+    }
+
+    pub fn ctx(&self) -> &CtxRef {
+        &self.ctx
+    }
+
+    pub fn set_size(&mut self,size : (u32,u32)) {
+        self.size = size;
+    }
+
+    pub fn set_clear_color(&mut self,color : Color32) {
+        self.clear_color = color;
+    }
+
+    /// Register an image shown by `ui.image`
+    pub fn register_texture(&mut self,image : RgbaImage) -> TextureId {
+        let id = self.next_texture;
+        self.next_texture += 1;
+        self.textures.insert(id, image);
+        TextureId::User(id)
+    }
+
+    pub fn free_texture(&mut self,id : TextureId) {
+        if let TextureId::User(id) = id {
+            self.textures.remove(&id);
+        }
+    }
+
+    /// Fed to the next frame,positions are in points
+    pub fn push_event(&mut self,event : egui::Event) {
+        self.raw_input.events.push(event);
+    }
+
+    /// Run a frame at `time` seconds and rasterize it.
+    /// Animations depend on the time,pass fixed values to get the same image every run
+    pub fn run(&mut self,time : f64,ui : impl FnOnce(&CtxRef)) -> (egui::Output,RgbaImage) {
+        let size_points = egui::vec2(
+            self.size.0 as f32 / self.pixels_per_point,
+            self.size.1 as f32 / self.pixels_per_point);
+        self.raw_input.pixels_per_point = Some(self.pixels_per_point);
+        self.raw_input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, size_points));
+        self.raw_input.time = Some(time);
+
+        self.ctx.begin_frame(self.raw_input.take());
+        ui(&self.ctx);
+        let (output,shapes) = self.ctx.end_frame();
+        let meshes = self.ctx.tessellate(shapes);
+
+        let mut target = Target::new(self.size.0 as usize, self.size.1 as usize, self.clear_color);
+        let font_image = self.ctx.font_image();
+        for mesh in meshes.iter() {
+            target.draw(mesh, &font_image, &self.textures, self.pixels_per_point);
+        }
+        (output,target.finish())
+    }
+}
+
+// premultiplied colors in gamma space,
+// it differs from the gpu blending in linear space by a little
+struct Target {
+    width : usize,
+    height : usize,
+    pixels : Vec<[f32;4]>,
+}
+
+fn to_rgba(color : Color32) -> [f32;4] {
+    let [r,g,b,a] = color.to_array();
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0]
+}
+
+// nearest texel,premultiplied
+fn sample(texture : TextureId,uv : Pos2,font_image : &FontImage,textures : &HashMap<u64,RgbaImage>) -> [f32;4] {
+    match texture {
+        TextureId::Egui => {
+            let x = ((uv.x * font_image.width as f32) as usize).min(font_image.width - 1);
+            let y = ((uv.y * font_image.height as f32) as usize).min(font_image.height - 1);
+            let alpha = font_image.pixels[y * font_image.width + x] as f32 / 255.0;
+            [alpha,alpha,alpha,alpha]
+        },
+        TextureId::User(id) => match textures.get(&id) {
+            Some(image) if image.width > 0 && image.height > 0 => {
+                let x = ((uv.x * image.width as f32) as usize).min(image.width - 1);
+                let y = ((uv.y * image.height as f32) as usize).min(image.height - 1);
+                to_rgba(image.get(x, y))
+            },
+            // freed textures are drawn with the vertex color
+            _ => [1.0,1.0,1.0,1.0]
+        }
+    }
+}
+
+fn edge(a : Pos2,b : Pos2,p : Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+// top-left fill rule,a pixel on an edge shared by two triangles is drawn once.
+// the inside of a->b is the positive side of `edge`
+fn covers(weight : f32,a : Pos2,b : Pos2) -> bool {
+    let top = a.y == b.y && b.x > a.x;
+    let left = b.y < a.y;
+    weight > 0.0 || (weight == 0.0 && (top || left))
+}
+
+impl Target {
+    fn new(width : usize,height : usize,clear_color : Color32) -> Self {
+        Target {
+            width,
+            height,
+            pixels: vec![to_rgba(clear_color); width * height],
+        }
+    }
+
+    fn draw(
+        &mut self,
+        ClippedMesh(clip_rect,mesh) : &ClippedMesh,
+        font_image : &FontImage,
+        textures : &HashMap<u64,RgbaImage>,
+        pixels_per_point : f32) {
+        // the same rounding as the scissor rect of the gpu backend
+        let clip_min_x = ((clip_rect.min.x * pixels_per_point).round().max(0.0) as usize).min(self.width);
+        let clip_min_y = ((clip_rect.min.y * pixels_per_point).round().max(0.0) as usize).min(self.height);
+        let clip_max_x = ((clip_rect.max.x * pixels_per_point).round().max(0.0) as usize).min(self.width);
+        let clip_max_y = ((clip_rect.max.y * pixels_per_point).round().max(0.0) as usize).min(self.height);
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let mut vertices = [
+                &mesh.vertices[triangle[0] as usize],
+                &mesh.vertices[triangle[1] as usize],
+                &mesh.vertices[triangle[2] as usize],
+            ];
+            let mut positions = vertices.map(|vertex|(vertex.pos.to_vec2() * pixels_per_point).to_pos2());
+            let mut area = edge(positions[0], positions[1], positions[2]);
+            if area == 0.0 {
+                continue;
+            }
+            // both windings are drawn,turn them into the same one
+            if area < 0.0 {
+                vertices.swap(1, 2);
+                positions.swap(1, 2);
+                area = -area;
+            }
+            let [p0,p1,p2] = positions;
+
+            let min_x = positions.iter().map(|p|p.x).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+            let min_y = positions.iter().map(|p|p.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+            let max_x = positions.iter().map(|p|p.x).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize;
+            let max_y = positions.iter().map(|p|p.y).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize;
+
+            for y in min_y.max(clip_min_y)..max_y.min(clip_max_y) {
+                for x in min_x.max(clip_min_x)..max_x.min(clip_max_x) {
+                    // sample at the pixel center
+                    let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                    let w0 = edge(p1, p2, p);
+                    let w1 = edge(p2, p0, p);
+                    let w2 = edge(p0, p1, p);
+                    if !(covers(w0, p1, p2) && covers(w1, p2, p0) && covers(w2, p0, p1)) {
+                        continue;
+                    }
+                    // barycentric weights
+                    let weights = [w0 / area, w1 / area, w2 / area];
+                    self.blend(x, y, shade(vertices, weights, mesh.texture_id, font_image, textures));
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self,x : usize,y : usize,src : [f32;4]) {
+        let dst = &mut self.pixels[y * self.width + x];
+        for (dst,src_) in dst.iter_mut().zip(src) {
+            *dst = src_ + *dst * (1.0 - src[3]);
+        }
+    }
+
+    fn finish(self) -> RgbaImage {
+        let mut pixels = Vec::with_capacity(self.width * self.height * 4);
+        for [r,g,b,a] in self.pixels {
+            let color = Color32::from_rgba_premultiplied(
+                (r * 255.0).round() as u8,
+                (g * 255.0).round() as u8,
+                (b * 255.0).round() as u8,
+                (a * 255.0).round() as u8);
+            pixels.extend_from_slice(&Rgba::from(color).to_srgba_unmultiplied());
+        }
+        RgbaImage {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+fn shade(
+    vertices : [&Vertex;3],
+    weights : [f32;3],
+    texture : TextureId,
+    font_image : &FontImage,
+    textures : &HashMap<u64,RgbaImage>) -> [f32;4] {
+    let mut uv = egui::Vec2::ZERO;
+    let mut color = [0.0;4];
+    for (vertex,weight) in vertices.iter().zip(weights) {
+        uv += vertex.uv.to_vec2() * weight;
+        for (color,vertex_color) in color.iter_mut().zip(to_rgba(vertex.color)) {
+            *color += vertex_color * weight;
+        }
+    }
+    let texel = sample(texture, uv.to_pos2(), font_image, textures);
+    [
+        color[0] * texel[0],
+        color[1] * texel[1],
+        color[2] * texel[2],
+        color[3] * texel[3],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Align2, Color32, LayerId, Rect, TextStyle, pos2};
+    use super::{EguiHeadless, RgbaImage};
+
+    const CLEAR : Color32 = Color32::from_rgb(10, 20, 30);
+
+    fn headless() -> EguiHeadless {
+        let mut headless = EguiHeadless::new((64,64), 1.0);
+        headless.set_clear_color(CLEAR);
+        headless
+    }
+
+    #[test]
+    fn clear_color() {
+        let (_,image) = headless().run(0.0, |_|{});
+        assert_eq!(image.diff(&RgbaImage::new(64, 64, CLEAR), 0), 0);
+    }
+
+    #[test]
+    fn filled_rect() {
+        let (_,image) = headless().run(0.0, |ctx|{
+            ctx.layer_painter(LayerId::background())
+                .rect_filled(Rect::from_min_max(pos2(10.0, 10.0), pos2(30.0, 30.0)), 0.0, Color32::RED);
+        });
+        assert_eq!(image.get(20, 20), Color32::RED);
+        assert_eq!(image.get(12, 27), Color32::RED);
+        // outside of the feathering
+        assert_eq!(image.get(5, 5), CLEAR);
+        assert_eq!(image.get(40, 40), CLEAR);
+    }
+
+    #[test]
+    fn translucent_rect_has_no_seam() {
+        let (_,image) = headless().run(0.0, |ctx|{
+            ctx.layer_painter(LayerId::background())
+                .rect_filled(
+                    Rect::from_min_max(pos2(10.0, 10.0), pos2(30.0, 30.0)),
+                    0.0,
+                    Color32::from_rgba_unmultiplied(255, 0, 0, 128));
+        });
+        let inside = image.get(20, 12);
+        assert_ne!(inside, CLEAR);
+        // the diagonal shared by the two triangles
+        for i in 12..28 {
+            assert_eq!(image.get(i, i), inside, "({},{})", i, i);
+            assert_eq!(image.get(i, 39 - i), inside, "({},{})", i, 39 - i);
+        }
+    }
+
+    #[test]
+    fn text_coverage() {
+        let mut headless = headless();
+        headless.set_clear_color(Color32::WHITE);
+        let mut text_rect = Rect::NOTHING;
+        let (_,image) = headless.run(0.0, |ctx|{
+            text_rect = ctx.layer_painter(LayerId::background())
+                .text(pos2(4.0, 4.0), Align2::LEFT_TOP, "Hello", TextStyle::Body, Color32::BLACK);
+        });
+        let mut covered = 0;
+        for y in 0..64 {
+            for x in 0..64 {
+                let pixel = image.get(x, y);
+                if pixel != Color32::WHITE {
+                    covered += 1;
+                    assert!(text_rect.expand(1.0).contains(pos2(x as f32 + 0.5, y as f32 + 0.5)), "({},{})", x, y);
+                }
+            }
+        }
+        assert!(covered > 0);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let (_,image) = headless().run(0.0, |ctx|{
+            ctx.layer_painter(LayerId::background())
+                .rect_filled(Rect::from_min_max(pos2(8.0, 8.0), pos2(24.0, 40.0)), 4.0, Color32::GREEN);
+        });
+        let path = std::env::temp_dir().join("elikar_egui_png_round_trip.png");
+        image.save_png(&path).unwrap();
+        let loaded = RgbaImage::load_png(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded, image);
+    }
+}
@@ -0,0 +1,132 @@
+use std::{pin::Pin, sync::Arc, task::{Context, Poll}};
+use futures::Stream;
+use parking_lot::RwLock;
+use xecs::{entity::EntityId, system::System, world::World};
+use crate::target::Target;
+
+/// Registered in world by `build`,updated after every painted frame.
+/// It is attached to the window if egui was built for one
+#[derive(Debug,Default,Clone,Copy,PartialEq,Eq)]
+pub struct EguiInputState {
+    /// egui is interested in the pointer,e.g. it is over a window
+    pub wants_pointer : bool,
+    /// egui is interested in the keyboard,e.g. a text field has focus
+    pub wants_keyboard : bool,
+    pub is_pointer_over_area : bool,
+    /// egui is dragging something
+    pub is_using_pointer : bool,
+    /// the window has keyboard focus
+    pub has_focus : bool,
+}
+
+impl EguiInputState {
+    pub(crate) fn from_ctx(ctx : &egui::CtxRef,has_focus : bool) -> Self {
+        EguiInputState {
+            wants_pointer: ctx.wants_pointer_input(),
+            wants_keyboard: ctx.wants_keyboard_input(),
+            is_pointer_over_area: ctx.is_pointer_over_area(),
+            is_using_pointer: ctx.is_using_pointer(),
+            has_focus,
+        }
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ExtraButton {
+    /// Usually "back"
+    X1,
+    /// Usually "forward"
+    X2,
+}
+
+/// Registered in world by `build` like `EguiInputState`,updated at the beginning of every frame.
+/// egui has no pointer buttons for X1 and X2,UI code reads them here
+#[derive(Debug,Default,Clone,Copy,PartialEq,Eq)]
+pub struct EguiExtraButtons {
+    pub x1_down : bool,
+    pub x2_down : bool,
+    /// pressed in this frame
+    pub x1_pressed : bool,
+    pub x2_pressed : bool,
+}
+
+impl EguiExtraButtons {
+    pub(crate) fn begin_frame(&mut self) {
+        self.x1_pressed = false;
+        self.x2_pressed = false;
+    }
+
+    pub(crate) fn update(&mut self,button : ExtraButton,pressed : bool) {
+        let (down,was_pressed) = match button {
+            ExtraButton::X1 => (&mut self.x1_down,&mut self.x1_pressed),
+            ExtraButton::X2 => (&mut self.x2_down,&mut self.x2_pressed),
+        };
+        *down = pressed;
+        *was_pressed |= pressed;
+    }
+}
+
+// Drops the events consumed by egui
+pub struct Filtered<St> {
+    inner : Pin<Box<St>>,
+    consumed : fn(&EguiInputState) -> bool,
+    target : Target,
+}
+
+impl<St> Filtered<St> {
+    /// Follow the egui built for the window instead of the one in world resources
+    pub fn for_window(mut self,window : EntityId) -> Self {
+        self.target = Target::Window(window);
+        self
+    }
+}
+
+/// Drop the items while egui wants the keyboard,
+/// e.g. `keyboard_filtered(events.on_key_down())`
+pub fn keyboard_filtered<St : Stream + System>(stream : St) -> Filtered<St> {
+    Filtered {
+        inner: Box::pin(stream),
+        consumed: |state|state.wants_keyboard,
+        target: Target::World,
+    }
+}
+
+/// Drop the items while egui wants the pointer,
+/// e.g. `pointer_filtered(events.on_mouse_down())`
+pub fn pointer_filtered<St : Stream + System>(stream : St) -> Filtered<St> {
+    Filtered {
+        inner: Box::pin(stream),
+        consumed: |state|state.wants_pointer,
+        target: Target::World,
+    }
+}
+
+impl<St : Stream + System> Stream for Filtered<St> {
+    type Item = St::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let consumed = {
+                        let world = self.inner.as_ref().world();
+                        let world = world.read();
+                        self.target.read::<EguiInputState>(&world)
+                            .map(|state|(self.consumed)(&state))
+                            .unwrap_or(false)
+                    };
+                    if !consumed {
+                        return Poll::Ready(Some(item));
+                    }
+                },
+                poll => return poll
+            }
+        }
+    }
+}
+
+impl<St : Stream + System> System for Filtered<St> {
+    fn world(&self) -> Arc<RwLock<World>> {
+        self.inner.as_ref().world()
+    }
+}
@@ -1,196 +1,394 @@
-use std::{pin::Pin, sync::Arc, task::{Context, Poll}, time::Instant};
-use crossbeam::channel::{Receiver, bounded};
-use egui::{CtxRef, FontData, FontDefinitions, Pos2, RawInput, Rect};
-use futures::{Stream, StreamExt};
-use parking_lot::RwLock;
-use xecs::{system::System, world::World};
-use elikar::{clipboard::Clipboard, common::Spawner, events::{Events, Update}, ime::IME};
+use std::{cell::RefCell, pin::Pin, rc::Rc, sync::Arc, task::{Context, Poll}, time::Instant};
+use crossbeam::channel::{Receiver, bounded, unbounded};
+use egui::{CtxRef, Pos2, RawInput, Rect};
+use futures::{Stream, StreamExt, channel::mpsc};
+use parking_lot::{Mutex, RwLock};
+use sdl2_sys::{SDL_GetMouseFocus, SDL_GetWindowFlags, SDL_WindowFlags};
+use xecs::{query::WithId, system::System, world::World};
+use focus::WindowFocus;
+use target::Target;
+use elikar::{clipboard::Clipboard, common::Spawner, events::{Events, Update}, ime::IME, keyboard::Keyboard, mouse::Mouse, window::Window};
 
 mod events;
+mod config;
+mod present;
+mod painter;
+mod output;
+mod cursor;
+mod repeat;
+mod focus;
+mod dnd;
+mod sdl;
+mod touch;
+mod gamepad;
+mod textures;
+mod viewport;
+mod target;
+mod offscreen;
+#[cfg(feature = "headless")]
+mod headless;
+mod error;
+pub mod fonts;
+mod settings;
+pub mod input;
 
-pub fn build<S : Spawner>(spawner : &mut S,events : Events) -> Prepared {
-    let keydown_rx = events::keydown(spawner, events.clone());
-    let keyup_rx = events::keyup(spawner, events.clone());
-    let mousedown_rx = events::mouse_down(spawner, events.clone());
-    let mouseup_rx = events::mouse_up(spawner, events.clone());
-    let motion_rx = events::mouse_motion(spawner, events.clone());
-    let wheel_rx = events::mouse_wheel(spawner, events.clone());
-    let editing_rx = events::text_editing(spawner, events.clone());
-    let input_rx = events::text_input(spawner, events.clone());
-    // let resized_rx = events::window_resized(spawner, events.clone());
+pub use config::{EguiBuilder, EguiConfig, RenderOrder, WheelScroll};
+pub use repeat::KeyRepeat;
+pub use gamepad::{GamepadAxis, GamepadBindings, GamepadButton, NavAction};
+pub use present::LastPass;
+pub use painter::EguiPainter;
+pub use textures::EguiTextures;
+pub use viewport::EguiViewport;
+pub use offscreen::EguiOffscreen;
+#[cfg(feature = "headless")]
+pub use headless::{EguiHeadless, RgbaImage};
+pub use error::Error;
+pub use settings::EguiSettings;
+pub use input::{EguiInputState, EguiExtraButtons};
+pub use events::key_map;
+pub use dnd::dropped_files;
+pub use egui_wgpu_backend::ScreenDescriptor;
+
+pub fn build<S : Spawner>(spawner : &mut S,events : Events) -> Result<Prepared,Error> {
+    EguiBuilder::new().build(spawner, events)
+}
+
+pub(crate) fn build_with_config<S : Spawner>(spawner : &mut S,events : Events,config : EguiConfig) -> Result<Prepared,Error> {
+    let (output_tx,output_rx) = unbounded();
+    let pending = Arc::new(Mutex::new(None));
+
+    let EguiConfig {
+        fonts,
+        msaa_samples,
+        clear_color,
+        scale_factor,
+        wheel_scroll,
+        render_order,
+        ime_size,
+        key_repeat,
+        touch,
+        gamepad,
+        window,
+    } = config;
+    let target = window.map_or(Target::World, Target::Window);
+
+    // check everything up front,so that the tasks never panic
+    let (window_size,window_focused,pointer_inside,physical_size) = {
+        let world = events.world();
+        let mut world = world.write();
+        error::require::<wgpu::Device>(&world)?;
+        error::require::<wgpu::Queue>(&world)?;
+        target.require::<wgpu::SurfaceConfiguration>(&world)?;
+        if render_order != RenderOrder::Manual {
+            target.require::<wgpu::Surface>(&world)?;
+        }
+        error::require::<Clipboard>(&world)?;
+        error::require::<IME>(&world)?;
+        error::require::<Keyboard>(&world)?;
+        error::require::<Mouse>(&world)?;
+
+        // sdl sends no focus events until the focus changes,
+        // the window says where the keyboard and the mouse are now
+        let window_state = |window : &Window| {
+            let ptr = unsafe { window.window_ptr() };
+            let flags = unsafe { SDL_GetWindowFlags(ptr) };
+            (window.size(),
+             flags & SDL_WindowFlags::SDL_WINDOW_INPUT_FOCUS as u32 != 0,
+             unsafe { SDL_GetMouseFocus() } == ptr)
+        };
+        // events of the other windows are kept if there is only one
+        let (window_size,window_focused,pointer_inside) = match window {
+            Some(window) => world.query::<&Window>()
+                .with_id()
+                .find(|(id,_)|*id == window)
+                .map(|(_,window)|window_state(window)),
+            None => world.query::<&Window>()
+                .next()
+                .map(window_state),
+        }.ok_or(Error::NoWindow)?;
+
+        let (painter,physical_size) = {
+            let device = world.resource_read::<wgpu::Device>().ok_or_else(error::missing::<wgpu::Device>)?;
+            let surface_config = target.read::<wgpu::SurfaceConfiguration>(&world)
+                .ok_or_else(error::missing::<wgpu::SurfaceConfiguration>)?;
+            let painter = EguiPainter::new(
+                &device,
+                surface_config.format,
+                msaa_samples,
+                clear_color,
+                pending.clone(),
+                output_tx);
+            (painter,(surface_config.width,surface_config.height))
+        };
+        target.register(&mut world, painter);
+        target.register(&mut world, EguiSettings::default());
+        target.register(&mut world, EguiInputState::default());
+        target.register(&mut world, EguiExtraButtons::default());
+        (window_size,window_focused,pointer_inside,physical_size)
+    };
+
+    let dropped_repeats = Rc::new(RefCell::new(repeat::DroppedRepeats::default()));
+    let keydown_rx = events::keydown(
+        spawner,
+        events.clone(),
+        window,
+        key_repeat.pass_os_repeats(),
+        dropped_repeats.clone());
+    let keyup_rx = events::keyup(spawner, events.clone(), window);
+    let mousedown_rx = events::mouse_down(spawner, events.clone(), window);
+    let mouseup_rx = events::mouse_up(spawner, events.clone(), window);
+    let motion_rx = events::mouse_motion(spawner, events.clone(), window);
+    let touch_rx = if touch {
+        Some(events::touch())
+    } else {
+        None
+    };
+    let wheel_rx = events::mouse_wheel(spawner, events.clone(), window);
+    let extra_rx = events::extra_buttons(spawner, events.clone(), window);
+    let input_rx = events::text_input(spawner, events.clone(), window);
+    let resized_rx = events::window_resized(spawner, events.clone(), window);
+    let focus_rx = events::window_focus(spawner, events.clone(), window);
+    let drop_rx = events::drop_files(spawner, events.clone(), window);
+    let mut gamepad_rx = gamepad.as_ref()
+        .map(|_|gamepad::Controllers::open().map(|controllers|(controllers,events::gamepad())))
+        .transpose()?;
 
     let (prepared_tx,prepared_rx) = bounded(1);
-    let (renderer_tx,renderer_rx) = bounded(1);
 
     let events_ = events.clone();
     spawner.spawn_local(async move {
         let mut frame_start = events_.on_enter_frame();
         let world = frame_start.world();
         let mut ctx_ref = CtxRef::default();
-
-        let mut fonts = FontDefinitions::default();
-        fonts.font_data.insert(
-            "simfang".to_owned(),
-            FontData::from_static(
-                include_bytes!("..\\fonts\\simfang.ttf")
-                ));
-        fonts.fonts_for_family.get_mut(&egui::FontFamily::Proportional).unwrap()
-            .insert(0,"simfang".to_owned());
-        fonts.fonts_for_family.get_mut(&egui::FontFamily::Monospace).unwrap()
-            .push("simfang".to_owned());
         ctx_ref.set_fonts(fonts);
 
-        let (w,h) = {
-            let world = world.read();
-            let surface_config = world.resource_read::<wgpu::SurfaceConfiguration>().unwrap();
-            (surface_config.width,surface_config.height)
-        };
+        let mut window_size = window_size;
+        let mut physical_size = physical_size;
 
-        let mut raw_input = RawInput {
-            screen_rect: 
-                Some(Rect::from_two_pos(
-                        Pos2 { x:0.0, y:0.0 },
-                        Pos2 { x: w as _, y: h as _})),
-            pixels_per_point: None,
-            .. RawInput::default()
-        };
+        let mut raw_input = RawInput::default();
+        let mut platform = output::Platform::default();
+        let mut repeater = repeat::Repeater::new(key_repeat);
+        // fonts are not ready until the first frame,
+        // use the default size of body text before it
+        let mut row_height = 14.0;
+        let mut held = focus::Held::default();
+        // sdl sends no focus events until the focus changes,
+        // the windows not focused when built must not take touch,controllers and the ime
+        let mut has_focus = window_focused;
+        let mut pointer_inside = pointer_inside;
+        // the mouse left while dragging
+        let mut pointer_gone = false;
+        let mut dropping = dnd::Dropping::default();
+        let mut touches = touch::Touches::default();
+        let mut navigator = gamepad.map(gamepad::Navigator::new);
 
         let start_time = Instant::now();
         loop {
             // wait for frame start
-            if let Some(_) = frame_start.next().await{
+            if frame_start.next().await.is_some() {
+                // keep screen rect in sync with the window
+                while let Ok(new_size) = resized_rx.try_recv() {
+                    window_size = new_size;
+                }
+                if let Some(surface_config) = target.read::<wgpu::SurfaceConfiguration>(&world.read()) {
+                    physical_size = (surface_config.width,surface_config.height);
+                }
+                // drawable size / window size
+                // checked every frame,it changes when the window moves to another monitor
+                let native_scale = physical_size.0 as f32 / window_size.0 as f32;
+                let pixels_per_point = scale_factor.unwrap_or(native_scale);
+                // elikar reports positions in window coordinates
+                let window_scale = pixels_per_point / native_scale;
+
+                let physical_size_points = egui::vec2(
+                    physical_size.0 as f32 / pixels_per_point,
+                    physical_size.1 as f32 / pixels_per_point);
+
+                raw_input.pixels_per_point = Some(pixels_per_point);
+                raw_input.screen_rect =
+                    Some(Rect::from_two_pos(
+                            Pos2 { x:0.0, y:0.0 },
+                            physical_size_points.to_pos2()));
+
+                // handle output of the last painted frame
+                while let Ok((ctx,output)) = output_rx.try_recv() {
+                    let world = world.read();
+                    // nothing to do if a resource was removed
+                    let state = output::WindowState {
+                        window_scale,
+                        has_focus,
+                        pointer_inside,
+                    };
+                    let _ = output::handle_output(&world, target, &ctx, output, &mut platform, state, ime_size);
+                }
+
                 // hanle input
+                let now = Instant::now();
                 while let Ok(event) = keydown_rx.try_recv() {
+                    repeater.observe(&event, now);
                     raw_input.events.push(event)
                 }
                 while let Ok(event) = keyup_rx.try_recv() {
+                    repeater.observe(&event, now);
                     raw_input.events.push(event)
                 }
+                raw_input.events.extend(repeater.repeats(now));
                 while let Ok(event) = mousedown_rx.try_recv() {
-                    raw_input.events.push(event)
+                    raw_input.events.push(events::to_points(event, window_scale))
                 }
                 while let Ok(event) = mouseup_rx.try_recv() {
-                    raw_input.events.push(event)
+                    raw_input.events.push(events::to_points(event, window_scale))
                 }
                 while let Ok(event) = motion_rx.try_recv() {
-                    raw_input.events.push(event)
+                    raw_input.events.push(events::to_points(event, window_scale))
+                }
+                // touch screens and controllers belong to no window,
+                // the focused one takes them
+                // the watch lives as long as this task
+                if let Some((_,touch_rx)) = touch_rx.as_ref() {
+                    while let Ok(finger) = touch_rx.try_recv() {
+                        if has_focus {
+                            raw_input.events.extend(touches.handle(finger, physical_size_points, now));
+                        }
+                    }
+                    raw_input.events.extend(touches.long_press(now));
+                }
+                // the controllers stay open and watched as long as this task
+                if let (Some((controllers,(_,gamepad_rx))),Some(navigator)) = (gamepad_rx.as_mut(),navigator.as_mut()) {
+                    while let Ok(pad) = gamepad_rx.try_recv() {
+                        controllers.handle(&pad);
+                        if has_focus {
+                            raw_input.events.extend(navigator.handle(pad));
+                        }
+                    }
                 }
+                let points_per_tick = match wheel_scroll {
+                    WheelScroll::Points(points) => egui::Vec2::splat(points),
+                    WheelScroll::Lines(lines) => egui::Vec2::splat(lines * row_height),
+                    WheelScroll::Pages(pages) => pages * physical_size_points,
+                };
                 while let Ok(event) = wheel_rx.try_recv() {
-                    raw_input.events.push(event)
+                    raw_input.events.push(events::scale_scroll(event, points_per_tick))
                 }
-                while let Ok(event) = input_rx.try_recv() {
-                    raw_input.events.push(event)
+                if let Some(mut extra_buttons) = target.write::<EguiExtraButtons>(&world.read()) {
+                    extra_buttons.begin_frame();
+                    while let Ok((button,pressed)) = extra_rx.try_recv() {
+                        extra_buttons.update(button, pressed);
+                    }
                 }
-                while let Ok(event) = editing_rx.try_recv() {
-                    raw_input.events.push(event)
+                // a repeat and its text arrive in the same frame
+                {
+                    let mut dropped_repeats = dropped_repeats.borrow_mut();
+                    while let Ok((timestamp,event)) = input_rx.try_recv() {
+                        if let egui::Event::Text(text) = &event {
+                            if dropped_repeats.is_repeated_text(timestamp) {
+                                continue;
+                            }
+                            repeater.observe_text(text);
+                        }
+                        raw_input.events.push(event)
+                    }
+                    dropped_repeats.clear();
+                }
+                for event in raw_input.events.iter() {
+                    held.observe(event);
+                }
+                while let Ok(focus) = focus_rx.try_recv() {
+                    match focus {
+                        // hover highlights stick without it,
+                        // but the drag goes on until the button was released
+                        WindowFocus::Leave => {
+                            pointer_inside = false;
+                            pointer_gone = true;
+                        },
+                        WindowFocus::Lost => {
+                            has_focus = false;
+                            // egui never sees the releases happening in other windows
+                            raw_input.events.extend(held.release_all());
+                            raw_input.events.push(egui::Event::PointerGone);
+                            repeater.release_all();
+                        },
+                        WindowFocus::Gained => has_focus = true,
+                        WindowFocus::Enter => {
+                            pointer_inside = true;
+                            pointer_gone = false;
+                        },
+                    }
+                }
+                if pointer_gone && !held.is_dragging() {
+                    pointer_gone = false;
+                    raw_input.events.push(egui::Event::PointerGone);
+                }
+                // after the pointer was gone,drops move it back
+                while let Ok(drop) = drop_rx.try_recv() {
+                    dropping.handle(drop, &mut raw_input, window_scale);
+                }
+                dropping.hover(&mut raw_input);
+
+                // changes made by other systems in the last frame
+                if let Some(mut settings) = target.write::<EguiSettings>(&world.read()) {
+                    settings.apply(&ctx_ref);
+                }
+                if let Some(navigator) = navigator.as_ref() {
+                    navigator.highlight(&ctx_ref);
                 }
 
                 raw_input.time = Some(start_time.elapsed().as_secs_f64());
                 ctx_ref.begin_frame(raw_input.take());
+                row_height = ctx_ref.fonts().row_height(egui::TextStyle::Body);
 
-                renderer_tx.send(ctx_ref.clone()).unwrap();
-                prepared_tx.send(ctx_ref.clone()).unwrap();
+                *pending.lock() = Some(ctx_ref.clone());
+                // skip this frame if nobody is waiting for it
+                let _ = prepared_tx.try_send(ctx_ref.clone());
             }
         }
     });
     
+    // other render systems hand their frame over to egui
+    let mut frames = match render_order {
+        RenderOrder::LastPass => {
+            let (tx,rx) = mpsc::unbounded();
+            let world = events.world();
+            target.register(&mut world.write(), LastPass { tx });
+            Some(rx)
+        }
+        _ => None
+    };
+
     // renderer
-    let events_ = events.clone();
-    spawner.spawn_local(async move {
-        let world = events_.world();
-        let renderer_rx = renderer_rx;
-        let mut render_pass = {
-            let world = world.read();
-            // create a render pass
-            let device = world.resource_read::<wgpu::Device>().unwrap();
-            let surface_config = world.resource_read::<wgpu::SurfaceConfiguration>().unwrap();
-            egui_wgpu_backend::RenderPass::new(&device,surface_config.format,1)
-        };
+    // paint into the surface and present it
+    if render_order != RenderOrder::Manual {
+        let events_ = events.clone();
+        spawner.spawn_local(async move {
+            let world = events_.world();
 
-        // wait for render stage
-        let mut render = events_.on_render();
-        while let Some(_) = render.next().await {
-
-            let world = world.read();
-            // Unwrap never fails:
-            // Render stage is behind of update stage
-            // This Task was ran in local 
-            // so this task must be run after update stage
-            // so there must be a exact one valid number
-            let ctx = renderer_rx.recv().unwrap();
-            let font_image = ctx.font_image();
-            let (output,shapes) = ctx.end_frame();
-            let mesh = ctx.tessellate(shapes);
-
-            // hanle output
-            // copy to clipboard
-            if !output.copied_text.is_empty() {
-                let mut clipboard = world.resource_write::<Clipboard>().unwrap();
-                clipboard.set(&output.copied_text).unwrap();
-            }
-            // ime
-            {
-                let mut ime = world.resource_write::<IME>().unwrap();
-                if ctx.wants_keyboard_input() {
-                    ime.start();
-                } else {
-                    ime.stop();
-                }
-                if ime.is_active() {
-                    if let Some(cursor) = output.text_cursor_pos {
-                        ime.set_area(cursor.x as _,cursor.y as _, 100, 50);
-                    }
+            // wait for render stage
+            let mut render = events_.on_render();
+            while render.next().await.is_some() {
+                // wait for the last render system
+                // world must not be locked here,it presents the frame
+                let frame = match frames.as_mut() {
+                    Some(frames) => match frames.next().await {
+                        Some(frame) => Some(frame),
+                        None => break
+                    },
+                    None => None
+                };
+
+                let world = world.read();
+                // nothing can be rendered any more,other errors skip this frame
+                if let Err(Error::Surface(wgpu::SurfaceError::OutOfMemory)) = present::present(&world, target, frame) {
+                    break;
                 }
             }
-
-            // render
-            let surface = world.resource_read::<wgpu::Surface>().unwrap();
-            let output = surface.get_current_texture().unwrap();
-            let output_view = output.texture
-                .create_view(&wgpu::TextureViewDescriptor::default());
-
-            let device = world.resource_read::<wgpu::Device>().unwrap();
-            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
-                label: Some("egui_renderer_encoder"),
-            });
-
-            let surface_config = world.resource_read::<wgpu::SurfaceConfiguration>().unwrap();
-            
-            let screen_desc = egui_wgpu_backend::ScreenDescriptor{
-                physical_width: surface_config.width,
-                physical_height: surface_config.height,
-                scale_factor: 1.0,
-            };
-            let queue = world.resource_read::<wgpu::Queue>().unwrap();
-
-            render_pass.update_texture(&device, &queue, &font_image);
-            render_pass.update_user_textures(&device,&queue);
-            render_pass.update_buffers(
-                &device,
-                &queue,
-                &mesh,
-                &screen_desc);
-
-            render_pass.execute(
-                &mut encoder,
-                &output_view,
-                &mesh,
-                &screen_desc,
-                Some(wgpu::Color::WHITE)
-            ).unwrap();
-
-            
-            queue.submit([encoder.finish()]);
-
-            output.present();
-        }
-    });
+        });
+    }
 
     let update = events.on_update();
-    Prepared{
+    Ok(Prepared{
         rx : prepared_rx,
         inner: Box::pin(update),
-    }
+    })
 }
 
 // Ready when update stage and handled all events
@@ -203,7 +401,7 @@ impl Stream for Prepared {
     type Item = CtxRef;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        if let Poll::Ready(_) = self.inner.as_mut().poll_next(cx) {
+        if self.inner.as_mut().poll_next(cx).is_ready() {
             if let Ok(ctx_ref) = self.rx.try_recv() {
                 return Poll::Ready(Some(ctx_ref));
             }
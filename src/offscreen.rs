@@ -0,0 +1,144 @@
+use std::{sync::Arc, time::Instant};
+use crossbeam::channel::{Receiver, unbounded};
+use egui::{CtxRef, Modifiers, Pos2, RawInput, Rect};
+use egui_wgpu_backend::BackendError;
+use parking_lot::Mutex;
+use crate::{EguiPainter, fonts};
+
+/// An egui context drawn into a texture owned by the caller,
+/// e.g. a screen in the game world.
+/// It knows nothing about windows,feed it with the `pointer_*` methods
+/// or `push_event`,pointer positions are in the uv space of the texture
+pub struct EguiOffscreen {
+    ctx : CtxRef,
+    raw_input : RawInput,
+    painter : EguiPainter,
+    pending : Arc<Mutex<Option<CtxRef>>>,
+    output_rx : Receiver<(CtxRef,egui::Output)>,
+    size : (u32,u32),
+    pixels_per_point : f32,
+    start_time : Instant,
+}
+
+impl EguiOffscreen {
+    /// `size` and `format` are of the target texture,
+    /// it needs the `RENDER_ATTACHMENT` usage.
+    /// The texture is cleared to `clear_color` before drawing,`None` draws on top of its contents
+    pub fn new(
+        device : &wgpu::Device,
+        format : wgpu::TextureFormat,
+        size : (u32,u32),
+        pixels_per_point : f32,
+        clear_color : Option<wgpu::Color>) -> Self {
+        let (output_tx,output_rx) = unbounded();
+        let pending = Arc::new(Mutex::new(None));
+        let painter = EguiPainter::new(
+            device,
+            format,
+            1,
+            clear_color,
+            pending.clone(),
+            output_tx);
+        let ctx = CtxRef::default();
+        ctx.set_fonts(fonts::default_fonts());
+        EguiOffscreen {
+            ctx,
+            raw_input: RawInput::default(),
+            painter,
+            pending,
+            output_rx,
+            size,
+            pixels_per_point,
+            start_time: Instant::now(),
+        }
+    }
+
+    pub fn ctx(&self) -> &CtxRef {
+        &self.ctx
+    }
+
+    /// Register user textures or change the clear color with `set_clear_color`
+    pub fn painter_mut(&mut self) -> &mut EguiPainter {
+        &mut self.painter
+    }
+
+    pub fn size(&self) -> (u32,u32) {
+        self.size
+    }
+
+    /// The target texture was recreated with another size
+    pub fn set_size(&mut self,size : (u32,u32)) {
+        self.size = size;
+    }
+
+    pub fn set_pixels_per_point(&mut self,pixels_per_point : f32) {
+        self.pixels_per_point = pixels_per_point;
+    }
+
+    fn size_points(&self) -> egui::Vec2 {
+        egui::vec2(
+            self.size.0 as f32 / self.pixels_per_point,
+            self.size.1 as f32 / self.pixels_per_point)
+    }
+
+    fn to_points(&self,uv : Pos2) -> Pos2 {
+        (uv.to_vec2() * self.size_points()).to_pos2()
+    }
+
+    /// Any event,positions must be in points
+    pub fn push_event(&mut self,event : egui::Event) {
+        self.raw_input.events.push(event);
+    }
+
+    /// The ray hits the texture at `uv`
+    pub fn pointer_moved(&mut self,uv : Pos2) {
+        let pos = self.to_points(uv);
+        self.push_event(egui::Event::PointerMoved(pos));
+    }
+
+    pub fn pointer_button(&mut self,uv : Pos2,button : egui::PointerButton,pressed : bool,modifiers : Modifiers) {
+        let pos = self.to_points(uv);
+        self.push_event(egui::Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers,
+        });
+    }
+
+    /// The ray does not hit the texture any more
+    pub fn pointer_gone(&mut self) {
+        self.push_event(egui::Event::PointerGone);
+    }
+
+    /// Scroll by `delta` points
+    pub fn scroll(&mut self,delta : egui::Vec2) {
+        self.push_event(egui::Event::Scroll(delta));
+    }
+
+    /// Begin a frame with the events pushed since the last one,
+    /// draw the ui with the returned context before `paint`
+    pub fn begin_frame(&mut self) -> CtxRef {
+        self.raw_input.pixels_per_point = Some(self.pixels_per_point);
+        self.raw_input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, self.size_points()));
+        self.raw_input.time = Some(self.start_time.elapsed().as_secs_f64());
+        self.ctx.begin_frame(self.raw_input.take());
+        *self.pending.lock() = Some(self.ctx.clone());
+        self.ctx.clone()
+    }
+
+    /// End the frame and record it into `encoder`,drawing on `target`.
+    /// The output is for the caller,e.g. the copied text and the cursor.
+    /// `None` if no frame began since the last call
+    pub fn paint(
+        &mut self,
+        device : &wgpu::Device,
+        queue : &wgpu::Queue,
+        encoder : &mut wgpu::CommandEncoder,
+        target : &wgpu::Texture) -> Result<Option<egui::Output>,BackendError> {
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let screen_desc = self.painter.screen_descriptor(self.size.0, self.size.1);
+        self.painter.paint(device, queue, encoder, &view, &screen_desc)?;
+        Ok(self.output_rx.try_iter().last().map(|(_,output)|output))
+    }
+}
@@ -0,0 +1,86 @@
+use egui::CtxRef;
+use elikar::{clipboard::Clipboard, ime::IME, mouse::Mouse};
+use xecs::world::World;
+use crate::{EguiInputState, cursor, error::{Error, missing}, target::Target};
+
+// platform states kept by the frame task
+#[derive(Default)]
+pub(crate) struct Platform {
+    captured : bool,
+}
+
+// the window in the frame the output is handled
+#[derive(Debug,Clone,Copy)]
+pub(crate) struct WindowState {
+    pub(crate) window_scale : f32,
+    pub(crate) has_focus : bool,
+    pub(crate) pointer_inside : bool,
+}
+
+impl Platform {
+    // keep receiving motions and the release outside the window while dragging
+    fn capture_mouse(&mut self,world : &World,using_pointer : bool) -> Result<(),Error> {
+        if using_pointer == self.captured {
+            return Ok(());
+        }
+        let mut mouse = world.resource_write::<Mouse>().ok_or_else(missing::<Mouse>)?;
+        let captured = if using_pointer { mouse.capture() } else { mouse.release() };
+        // not supported by the platform,dragging stops at the window border
+        if captured.is_ok() {
+            self.captured = using_pointer;
+        }
+        Ok(())
+    }
+}
+
+// apply the output of the last painted frame to the platform
+pub(crate) fn handle_output(
+    world : &World,
+    target : Target,
+    ctx : &CtxRef,
+    output : egui::Output,
+    platform : &mut Platform,
+    window : WindowState,
+    ime_size : Option<(f32,f32)>) -> Result<(),Error> {
+    let WindowState { window_scale, has_focus, pointer_inside } = window;
+    // let game systems know what egui consumed
+    if let Some(mut state) = target.write::<EguiInputState>(world) {
+        **state = EguiInputState::from_ctx(ctx, has_focus);
+    }
+    // the cursor and the ime are shared by all the windows
+    if pointer_inside || platform.captured {
+        cursor::set(output.cursor_icon);
+        platform.capture_mouse(world, ctx.is_using_pointer())?;
+    }
+    // copy to clipboard
+    if !output.copied_text.is_empty() {
+        let mut clipboard = world.resource_write::<Clipboard>().ok_or_else(missing::<Clipboard>)?;
+        // the copied text is lost,but that's not worth crashing the game
+        let _ = clipboard.set(&output.copied_text);
+    }
+    // ime
+    if has_focus {
+        let mut ime = world.resource_write::<IME>().ok_or_else(missing::<IME>)?;
+        // restarting the ime drops the composition
+        let wants_keyboard = ctx.wants_keyboard_input();
+        if wants_keyboard && !ime.is_active() {
+            ime.start();
+        } else if !wants_keyboard && ime.is_active() {
+            ime.stop();
+        }
+        if ime.is_active() {
+            // the cursor of the focused text edit,
+            // candidate window shows below it
+            if let Some(cursor) = output.text_cursor_pos {
+                let (width,height) = ime_size.unwrap_or_else(||(1.0,ctx.fonts().row_height(egui::TextStyle::Body)));
+                // ime area is in window coordinates
+                ime.set_area(
+                    (cursor.x * window_scale) as _,
+                    (cursor.y * window_scale) as _,
+                    (width * window_scale).ceil() as _,
+                    (height * window_scale).ceil() as _);
+            }
+        }
+    }
+    Ok(())
+}
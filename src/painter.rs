@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use crossbeam::channel::Sender;
+use egui::{ClippedMesh, CtxRef, FontImage, TextureId};
+use egui_wgpu_backend::{BackendError, RenderPass, ScreenDescriptor};
+use parking_lot::Mutex;
+use crate::EguiTextures;
+
+/// Encodes egui into a caller-supplied encoder and view.
+/// Registered in world by `build`,or attached to the window it was built for
+pub struct EguiPainter {
+    render_pass : RenderPass,
+    freed_textures : Vec<TextureId>,
+    clear_color : Option<wgpu::Color>,
+    // the frame began but not painted yet
+    pending : Arc<Mutex<Option<CtxRef>>>,
+    // outputs are handled in the next frame
+    output_tx : Sender<(CtxRef,egui::Output)>,
+    font_image : Option<Arc<FontImage>>,
+    meshes : Vec<ClippedMesh>,
+    pixels_per_point : f32,
+}
+
+impl EguiPainter {
+    pub(crate) fn new(
+        device : &wgpu::Device,
+        format : wgpu::TextureFormat,
+        msaa_samples : u32,
+        clear_color : Option<wgpu::Color>,
+        pending : Arc<Mutex<Option<CtxRef>>>,
+        output_tx : Sender<(CtxRef,egui::Output)>) -> Self {
+        EguiPainter {
+            render_pass: RenderPass::new(device,format,msaa_samples),
+            freed_textures: Vec::new(),
+            clear_color,
+            pending,
+            output_tx,
+            font_image: None,
+            meshes: Vec::new(),
+            pixels_per_point: 1.0,
+        }
+    }
+
+    pub fn clear_color(&self) -> Option<wgpu::Color> {
+        self.clear_color
+    }
+
+    /// `None` keeps the contents of the target and draws egui on top of it
+    pub fn set_clear_color(&mut self,color : Option<wgpu::Color>) {
+        self.clear_color = color;
+    }
+
+    /// Register,update and free the textures shown by `ui.image`
+    pub fn textures(&mut self) -> EguiTextures<'_> {
+        EguiTextures {
+            render_pass: &mut self.render_pass,
+            freed: &mut self.freed_textures,
+        }
+    }
+
+    /// Scale of the frame going to be painted
+    pub fn pixels_per_point(&self) -> f32 {
+        match self.pending.lock().as_ref() {
+            Some(ctx) => ctx.pixels_per_point(),
+            None => self.pixels_per_point
+        }
+    }
+
+    /// Describe a target of the given physical size for `paint`
+    pub fn screen_descriptor(&self,physical_width : u32,physical_height : u32) -> ScreenDescriptor {
+        ScreenDescriptor {
+            physical_width,
+            physical_height,
+            scale_factor: self.pixels_per_point(),
+        }
+    }
+
+    // end the pending frame,
+    // the previous meshes are kept if there is no new frame
+    fn end_frame(&mut self) {
+        let ctx = self.pending.lock().take();
+        if let Some(ctx) = ctx {
+            let (output,shapes) = ctx.end_frame();
+            self.font_image = Some(ctx.font_image());
+            self.pixels_per_point = ctx.pixels_per_point();
+            self.meshes = ctx.tessellate(shapes);
+            // the frame task was gone
+            let _ = self.output_tx.send((ctx,output));
+        }
+    }
+
+    /// Ends the current egui frame and records it into `encoder`,drawing on `target_view`
+    pub fn paint(
+        &mut self,
+        device : &wgpu::Device,
+        queue : &wgpu::Queue,
+        encoder : &mut wgpu::CommandEncoder,
+        target_view : &wgpu::TextureView,
+        screen_desc : &ScreenDescriptor) -> Result<(),BackendError> {
+        self.end_frame();
+        let font_image = match &self.font_image {
+            Some(font_image) => font_image,
+            // nothing began yet
+            None => return Ok(())
+        };
+
+        self.render_pass.update_texture(device, queue, font_image);
+        self.render_pass.update_user_textures(device, queue);
+        self.render_pass.update_buffers(
+            device,
+            queue,
+            &self.meshes,
+            screen_desc);
+
+        self.render_pass.execute(
+            encoder,
+            target_view,
+            &self.meshes,
+            screen_desc,
+            self.clear_color)
+    }
+}
@@ -0,0 +1,65 @@
+use futures::channel::mpsc::UnboundedSender;
+use xecs::world::World;
+use crate::{EguiPainter, error::{Error, missing}, target::Target};
+
+/// Registered in world when egui is built with `RenderOrder::LastPass`,
+/// attached to the window if it was built for one
+pub struct LastPass {
+    pub(crate) tx : UnboundedSender<wgpu::SurfaceTexture>
+}
+
+impl LastPass {
+    /// Hand the rendered frame over to egui instead of presenting it.
+    /// egui draws on top of it and presents it
+    pub fn present(&self,frame : wgpu::SurfaceTexture) {
+        // the renderer task was gone,drop the frame
+        let _ = self.tx.unbounded_send(frame);
+    }
+}
+
+// acquire the frame if no one handed it over,paint egui and present it
+pub(crate) fn present(world : &World,target : Target,frame : Option<wgpu::SurfaceTexture>) -> Result<(),Error> {
+    let device = world.resource_read::<wgpu::Device>().ok_or_else(missing::<wgpu::Device>)?;
+    let queue = world.resource_read::<wgpu::Queue>().ok_or_else(missing::<wgpu::Queue>)?;
+    let surface_config = target.read::<wgpu::SurfaceConfiguration>(world)
+        .ok_or_else(missing::<wgpu::SurfaceConfiguration>)?;
+    let mut painter = target.write::<EguiPainter>(world).ok_or_else(missing::<EguiPainter>)?;
+
+    let output = match frame {
+        Some(frame) => frame,
+        None => {
+            let surface = target.read::<wgpu::Surface>(world).ok_or_else(missing::<wgpu::Surface>)?;
+            match surface.get_current_texture() {
+                Ok(output) => output,
+                Err(error @ (wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost)) => {
+                    // the next frame will be fine
+                    surface.configure(&device, &surface_config);
+                    return Err(error.into());
+                },
+                Err(error) => return Err(error.into())
+            }
+        }
+    };
+    let output_view = output.texture
+        .create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor{
+        label: Some("egui_renderer_encoder"),
+    });
+
+    let screen_desc = painter.screen_descriptor(
+        surface_config.width,
+        surface_config.height);
+    painter.paint(
+        &device,
+        &queue,
+        &mut encoder,
+        &output_view,
+        &screen_desc
+    )?;
+
+    queue.submit([encoder.finish()]);
+
+    output.present();
+    Ok(())
+}
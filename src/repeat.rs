@@ -0,0 +1,221 @@
+use std::time::{Duration, Instant};
+use egui::{Key, Modifiers};
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum KeyRepeat {
+    /// Forward the repeats sent by the platform
+    Os,
+    /// Ignore the repeats sent by the platform
+    Off,
+    /// Ignore the repeats sent by the platform and repeat the held key by ourselves
+    Synthesize {
+        /// time between the press and the first repeat
+        delay : Duration,
+        /// time between two repeats
+        interval : Duration,
+    },
+}
+
+impl KeyRepeat {
+    pub(crate) fn pass_os_repeats(&self) -> bool {
+        matches!(self, KeyRepeat::Os)
+    }
+}
+
+struct Held {
+    key : Key,
+    modifiers : Modifiers,
+    next : Instant,
+    // the text typed by the press is repeated with the key
+    text : Option<String>,
+}
+
+// Repeats the key pressed last,like a native text box does.
+// egui 0.16 has no flag for repeats,
+// they are sent as presses without release between them
+pub(crate) struct Repeater {
+    mode : KeyRepeat,
+    held : Option<Held>,
+}
+
+impl Repeater {
+    pub(crate) fn new(mode : KeyRepeat) -> Self {
+        Repeater {
+            mode,
+            held: None,
+        }
+    }
+
+    // look at the key events sent to egui
+    pub(crate) fn observe(&mut self,event : &egui::Event,now : Instant) {
+        let delay = match self.mode {
+            KeyRepeat::Synthesize { delay, .. } => delay,
+            _ => return
+        };
+        if let egui::Event::Key { key, pressed, modifiers } = *event {
+            if pressed {
+                self.held = Some(Held {
+                    key,
+                    modifiers,
+                    next: now + delay,
+                    text: None,
+                });
+            } else if matches!(&self.held,Some(held) if held.key == key) {
+                self.held = None;
+            }
+        }
+    }
+
+    // the text input following the press of the held key
+    pub(crate) fn observe_text(&mut self,text : &str) {
+        if let Some(held) = self.held.as_mut() {
+            if held.text.is_none() {
+                held.text = Some(text.to_owned());
+            }
+        }
+    }
+
+    pub(crate) fn repeats(&mut self,now : Instant) -> Vec<egui::Event> {
+        let interval = match self.mode {
+            KeyRepeat::Synthesize { interval, .. } => interval,
+            _ => return Vec::new()
+        };
+        let mut events = Vec::new();
+        if let Some(held) = self.held.as_mut() {
+            // a long frame may catch up several repeats
+            while held.next <= now {
+                events.push(egui::Event::Key {
+                    key: held.key,
+                    pressed: true,
+                    modifiers: held.modifiers,
+                });
+                if let Some(text) = held.text.as_ref() {
+                    events.push(egui::Event::Text(text.clone()));
+                }
+                held.next += interval.max(Duration::from_millis(1));
+            }
+        }
+        events
+    }
+
+    // the window lost focus
+    pub(crate) fn release_all(&mut self) {
+        self.held = None;
+    }
+}
+
+// The os repeats of a held key ignored by `KeyRepeat::Off` and `Synthesize`.
+// sdl stamps a KEYDOWN and the TEXTINPUT it types with the same timestamp,
+// only the texts matching a dropped repeat are dropped.
+// Repeats typing nothing (shift,arrows) match no text,they are forgotten every frame
+#[derive(Default)]
+pub(crate) struct DroppedRepeats {
+    timestamps : Vec<u32>,
+}
+
+impl DroppedRepeats {
+    pub(crate) fn push(&mut self,timestamp : u32) {
+        self.timestamps.push(timestamp);
+    }
+
+    // the text was typed by a dropped repeat,each repeat types one text
+    pub(crate) fn is_repeated_text(&mut self,timestamp : u32) -> bool {
+        match self.timestamps.iter().position(|dropped|*dropped == timestamp) {
+            Some(index) => {
+                self.timestamps.swap_remove(index);
+                true
+            },
+            None => false
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.timestamps.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY : Duration = Duration::from_millis(500);
+    const INTERVAL : Duration = Duration::from_millis(30);
+
+    fn press(key : Key,pressed : bool) -> egui::Event {
+        egui::Event::Key {
+            key,
+            pressed,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    fn synthesize() -> Repeater {
+        Repeater::new(KeyRepeat::Synthesize {
+            delay: DELAY,
+            interval: INTERVAL,
+        })
+    }
+
+    #[test]
+    fn repeats_after_delay() {
+        let start = Instant::now();
+        let mut repeater = synthesize();
+        repeater.observe(&press(Key::A, true), start);
+        repeater.observe_text("a");
+
+        assert!(repeater.repeats(start + DELAY - INTERVAL).is_empty());
+        assert_eq!(
+            repeater.repeats(start + DELAY),
+            vec![press(Key::A, true), egui::Event::Text("a".to_owned())]);
+        // a long frame catches up
+        assert_eq!(repeater.repeats(start + DELAY + INTERVAL * 2).len(), 4);
+    }
+
+    #[test]
+    fn stops_on_release() {
+        let start = Instant::now();
+        let mut repeater = synthesize();
+        repeater.observe(&press(Key::A, true), start);
+        repeater.observe(&press(Key::B, true), start);
+        // releasing the key pressed before keeps the last one
+        repeater.observe(&press(Key::A, false), start);
+        assert_eq!(repeater.repeats(start + DELAY), vec![press(Key::B, true)]);
+
+        repeater.observe(&press(Key::B, false), start);
+        assert!(repeater.repeats(start + DELAY * 2).is_empty());
+
+        repeater.observe(&press(Key::C, true), start);
+        repeater.release_all();
+        assert!(repeater.repeats(start + DELAY * 2).is_empty());
+    }
+
+    #[test]
+    fn os_and_off_do_not_repeat() {
+        let start = Instant::now();
+        for mode in [KeyRepeat::Os, KeyRepeat::Off] {
+            let mut repeater = Repeater::new(mode);
+            repeater.observe(&press(Key::A, true), start);
+            assert!(repeater.repeats(start + DELAY * 2).is_empty());
+        }
+        assert!(KeyRepeat::Os.pass_os_repeats());
+        assert!(!KeyRepeat::Off.pass_os_repeats());
+    }
+
+    #[test]
+    fn drops_only_repeated_texts() {
+        let mut dropped = DroppedRepeats::default();
+        // a repeat of shift types nothing
+        dropped.push(10);
+        // a repeat of `a` and its text
+        dropped.push(20);
+
+        // typed by another key
+        assert!(!dropped.is_repeated_text(15));
+        assert!(dropped.is_repeated_text(20));
+        // one text for one repeat
+        assert!(!dropped.is_repeated_text(20));
+
+        dropped.clear();
+        assert!(!dropped.is_repeated_text(10));
+    }
+}
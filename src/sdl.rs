@@ -0,0 +1,36 @@
+use std::os::raw::{c_int, c_void};
+use sdl2_sys::{SDL_AddEventWatch, SDL_DelEventWatch, SDL_Event};
+
+type Callback = Box<dyn FnMut(&SDL_Event)>;
+
+// Sees the sdl events elikar does not dispatch,like touch and controllers.
+// sdl calls the watch when the event is pushed,in the thread polling the events,
+// which is the main thread of elikar.
+// Removed when dropped,keep it in the task using the events
+pub(crate) struct EventWatch {
+    callback : *mut Callback,
+}
+
+unsafe extern "C" fn call(userdata : *mut c_void,event : *mut SDL_Event) -> c_int {
+    let callback = &mut *(userdata as *mut Callback);
+    callback(&*event);
+    // ignored for watches
+    0
+}
+
+impl EventWatch {
+    pub(crate) fn new<F : FnMut(&SDL_Event) + 'static>(callback : F) -> Self {
+        let callback : *mut Callback = Box::into_raw(Box::new(Box::new(callback)));
+        unsafe { SDL_AddEventWatch(Some(call), callback as *mut c_void) };
+        EventWatch { callback }
+    }
+}
+
+impl Drop for EventWatch {
+    fn drop(&mut self) {
+        unsafe {
+            SDL_DelEventWatch(Some(call), self.callback as *mut c_void);
+            drop(Box::from_raw(self.callback));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use egui::{CtxRef, FontDefinitions, Style, Visuals};
+
+/// Registered in world by `build`,or attached to the window it was built for.
+/// Changes are applied to the context at the beginning of the next frame
+#[derive(Default)]
+pub struct EguiSettings {
+    fonts : Option<FontDefinitions>,
+    style : Option<Style>,
+    visuals : Option<Visuals>,
+}
+
+impl EguiSettings {
+    pub fn set_fonts(&mut self,fonts : FontDefinitions) {
+        self.fonts = Some(fonts);
+    }
+
+    /// Replace the whole style,including visuals
+    pub fn set_style(&mut self,style : Style) {
+        // the new style overrides the visuals set before
+        self.visuals = None;
+        self.style = Some(style);
+    }
+
+    pub fn set_visuals(&mut self,visuals : Visuals) {
+        self.visuals = Some(visuals);
+    }
+
+    pub fn set_dark_mode(&mut self,dark : bool) {
+        self.set_visuals(if dark { Visuals::dark() } else { Visuals::light() });
+    }
+
+    pub(crate) fn apply(&mut self,ctx : &CtxRef) {
+        if let Some(fonts) = self.fonts.take() {
+            ctx.set_fonts(fonts);
+        }
+        if let Some(style) = self.style.take() {
+            ctx.set_style(style);
+        }
+        if let Some(visuals) = self.visuals.take() {
+            ctx.set_visuals(visuals);
+        }
+    }
+}
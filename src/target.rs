@@ -0,0 +1,51 @@
+use std::ops::{Deref, DerefMut};
+use xecs::{entity::EntityId, world::World};
+use crate::error::{Error, missing};
+
+// where the per-window states of an egui instance live
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub(crate) enum Target {
+    // resources of world,for the only window
+    World,
+    // components of the window entity
+    Window(EntityId),
+}
+
+impl Target {
+    pub(crate) fn read<'a,T : Send + Sync + 'static>(self,world : &'a World) -> Option<Box<dyn Deref<Target = T> + 'a>> {
+        match self {
+            Target::World => world.resource_read::<T>()
+                .map(|resource|Box::new(resource) as Box<dyn Deref<Target = T>>),
+            Target::Window(id) => world.entity_component_read::<T>(id)
+                .map(|component|Box::new(component) as Box<dyn Deref<Target = T>>),
+        }
+    }
+
+    pub(crate) fn write<'a,T : Send + Sync + 'static>(self,world : &'a World) -> Option<Box<dyn DerefMut<Target = T> + 'a>> {
+        match self {
+            Target::World => world.resource_write::<T>()
+                .map(|resource|Box::new(resource) as Box<dyn DerefMut<Target = T>>),
+            Target::Window(id) => world.entity_component_write::<T>(id)
+                .map(|component|Box::new(component) as Box<dyn DerefMut<Target = T>>),
+        }
+    }
+
+    pub(crate) fn register<T : Send + Sync + 'static>(self,world : &mut World,value : T) {
+        match self {
+            Target::World => world.register_resource(value),
+            Target::Window(id) => {
+                // several windows share the component types
+                if !world.has_registered::<T>() {
+                    world.register::<T>();
+                }
+                world.attach_component(id, value);
+            },
+        }
+    }
+
+    pub(crate) fn require<T : Send + Sync + 'static>(self,world : &World) -> Result<(),Error> {
+        self.read::<T>(world)
+            .map(|_|())
+            .ok_or_else(missing::<T>)
+    }
+}
@@ -0,0 +1,130 @@
+use std::num::NonZeroU32;
+use egui::{Color32, TextureId};
+use egui_wgpu_backend::{BackendError, RenderPass};
+use crate::Error;
+
+/// User textures shown by `ui.image`,
+/// get it from `EguiPainter::textures`
+pub struct EguiTextures<'a> {
+    pub(crate) render_pass : &'a mut RenderPass,
+    // the render pass can't unregister textures,
+    // freed ids are bound to a 1x1 texture and reused by `register`
+    pub(crate) freed : &'a mut Vec<TextureId>,
+}
+
+fn check_size(size : (u32,u32),rgba : &[u8]) -> Result<(),Error> {
+    if size.0 == 0 || size.1 == 0 || rgba.len() as u64 != size.0 as u64 * size.1 as u64 * 4 {
+        return Err(Error::TextureSize { size, len: rgba.len() });
+    }
+    Ok(())
+}
+
+fn create(device : &wgpu::Device,size : (u32,u32)) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("egui_user_texture"),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    })
+}
+
+fn upload(
+    device : &wgpu::Device,
+    queue : &wgpu::Queue,
+    size : (u32,u32),
+    rgba : &[u8]) -> wgpu::Texture {
+    // egui blends premultiplied colors
+    let premultiplied = rgba.chunks_exact(4)
+        .flat_map(|p|Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]).to_array())
+        .collect::<Vec<u8>>();
+    let texture = create(device, size);
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &premultiplied,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: NonZeroU32::new(size.0 * 4),
+            rows_per_image: NonZeroU32::new(size.1),
+        },
+        wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        });
+    texture
+}
+
+impl<'a> EguiTextures<'a> {
+    /// Show an existing texture,e.g. a render target.
+    /// It must have `TEXTURE_BINDING` usage and premultiplied alpha
+    pub fn register(
+        &mut self,
+        device : &wgpu::Device,
+        texture : &wgpu::Texture,
+        filter : wgpu::FilterMode) -> TextureId {
+        if let Some(id) = self.freed.pop() {
+            if self.update(device, id, texture, filter).is_ok() {
+                return id;
+            }
+        }
+        self.render_pass.egui_texture_from_wgpu_texture(device, texture, filter)
+    }
+
+    /// Show another texture with the id,e.g. the render target was resized
+    pub fn update(
+        &mut self,
+        device : &wgpu::Device,
+        id : TextureId,
+        texture : &wgpu::Texture,
+        filter : wgpu::FilterMode) -> Result<(),BackendError> {
+        self.render_pass.update_egui_texture_from_wgpu_texture(device, texture, filter, id)
+    }
+
+    /// Upload sRGBA pixels,4 bytes per pixel and rows are tightly packed.
+    /// They are not premultiplied,the same as `RgbaImage` of the headless backend.
+    /// Fails if the size is empty or does not match the length of `rgba`
+    pub fn upload_rgba(
+        &mut self,
+        device : &wgpu::Device,
+        queue : &wgpu::Queue,
+        size : (u32,u32),
+        rgba : &[u8]) -> Result<TextureId,Error> {
+        check_size(size, rgba)?;
+        let texture = upload(device, queue, size, rgba);
+        Ok(self.register(device, &texture, wgpu::FilterMode::Linear))
+    }
+
+    /// Replace the pixels of the texture with the id
+    pub fn update_rgba(
+        &mut self,
+        device : &wgpu::Device,
+        queue : &wgpu::Queue,
+        id : TextureId,
+        size : (u32,u32),
+        rgba : &[u8]) -> Result<(),Error> {
+        check_size(size, rgba)?;
+        let texture = upload(device, queue, size, rgba);
+        Ok(self.update(device, id, &texture, wgpu::FilterMode::Linear)?)
+    }
+
+    /// Release the texture shown with the id,the id is reused by the next `register`.
+    /// The id must not be used any more
+    pub fn free(&mut self,device : &wgpu::Device,id : TextureId) -> Result<(),BackendError> {
+        let texture = create(device, (1,1));
+        self.update(device, id, &texture, wgpu::FilterMode::Nearest)?;
+        self.freed.push(id);
+        Ok(())
+    }
+}
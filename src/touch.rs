@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+use egui::{Modifiers, PointerButton, Pos2, TouchDeviceId, TouchId, TouchPhase, Vec2};
+
+// longer than the click duration of egui,
+// so that releasing after a long press is not a click
+const LONG_PRESS : Duration = Duration::from_millis(700);
+// a finger moving farther than it is not pressing long
+const LONG_PRESS_DISTANCE : f32 = 6.0;
+
+pub(crate) struct Finger {
+    pub(crate) device : u64,
+    pub(crate) id : u64,
+    pub(crate) phase : TouchPhase,
+    /// normalized to the window,from 0 to 1
+    pub(crate) position : (f32,f32),
+    pub(crate) force : f32,
+}
+
+// The first finger is the primary pointer,
+// two fingers pinch to zoom and pan to scroll
+#[derive(Default)]
+pub(crate) struct Touches {
+    fingers : Vec<(u64,Pos2)>,
+    primary : Option<u64>,
+    // where and when the primary finger was pressed,
+    // `None` if it moved or the long press was fired
+    press : Option<(Instant,Pos2)>,
+    // distance and center of two fingers
+    pinch : Option<(f32,Pos2)>,
+}
+
+fn pinch(a : Pos2,b : Pos2) -> (f32,Pos2) {
+    (a.distance(b),a + (b - a) / 2.0)
+}
+
+fn button(pos : Pos2,button : PointerButton,pressed : bool) -> egui::Event {
+    egui::Event::PointerButton {
+        pos,
+        button,
+        pressed,
+        modifiers: Modifiers::default(),
+    }
+}
+
+impl Touches {
+    pub(crate) fn handle(&mut self,finger : Finger,screen_size : Vec2,now : Instant) -> Vec<egui::Event> {
+        let pos = Pos2 {
+            x: finger.position.0 * screen_size.x,
+            y: finger.position.1 * screen_size.y,
+        };
+        let mut events = vec![egui::Event::Touch {
+            device_id: TouchDeviceId(finger.device),
+            id: TouchId(finger.id),
+            phase: finger.phase,
+            pos,
+            force: finger.force,
+        }];
+
+        match finger.phase {
+            TouchPhase::Start => {
+                self.fingers.push((finger.id,pos));
+                match self.fingers.len() {
+                    1 => {
+                        self.primary = Some(finger.id);
+                        self.press = Some((now,pos));
+                        events.push(egui::Event::PointerMoved(pos));
+                        events.push(button(pos, PointerButton::Primary, true));
+                    },
+                    2 => {
+                        // not a pointer any more
+                        if self.primary.take().is_some() {
+                            let (_,primary_pos) = self.fingers[0];
+                            events.push(button(primary_pos, PointerButton::Primary, false));
+                            events.push(egui::Event::PointerGone);
+                        }
+                        self.press = None;
+                        self.pinch = Some(pinch(self.fingers[0].1, self.fingers[1].1));
+                    },
+                    _ => self.pinch = None
+                }
+            },
+            TouchPhase::Move => {
+                if let Some((_,finger_pos)) = self.fingers.iter_mut().find(|(id,_)|*id == finger.id) {
+                    *finger_pos = pos;
+                }
+                if self.primary == Some(finger.id) {
+                    events.push(egui::Event::PointerMoved(pos));
+                    if matches!(self.press,Some((_,origin)) if origin.distance(pos) > LONG_PRESS_DISTANCE) {
+                        self.press = None;
+                    }
+                }
+                if let (Some((distance,center)),[(_,a),(_,b)]) = (self.pinch,self.fingers.as_slice()) {
+                    let (new_distance,new_center) = pinch(*a, *b);
+                    if distance > 0.0 {
+                        events.push(egui::Event::Zoom(new_distance / distance));
+                    }
+                    events.push(egui::Event::Scroll(new_center - center));
+                    self.pinch = Some((new_distance,new_center));
+                }
+            },
+            TouchPhase::End | TouchPhase::Cancel => {
+                self.fingers.retain(|(id,_)|*id != finger.id);
+                if self.primary == Some(finger.id) {
+                    self.primary = None;
+                    self.press = None;
+                    events.push(button(pos, PointerButton::Primary, false));
+                    events.push(egui::Event::PointerGone);
+                }
+                self.pinch = match self.fingers.as_slice() {
+                    [(_,a),(_,b)] => Some(pinch(*a, *b)),
+                    _ => None
+                };
+            },
+        }
+        events
+    }
+
+    // a long press is a secondary click
+    pub(crate) fn long_press(&mut self,now : Instant) -> Vec<egui::Event> {
+        match self.press {
+            Some((time,pos)) if now.duration_since(time) >= LONG_PRESS => {
+                self.press = None;
+                vec![
+                    button(pos, PointerButton::Secondary, true),
+                    button(pos, PointerButton::Secondary, false),
+                ]
+            },
+            _ => Vec::new()
+        }
+    }
+}
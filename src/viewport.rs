@@ -0,0 +1,144 @@
+use egui::{Rect, Response, Sense, TextureId, Ui};
+use egui_wgpu_backend::BackendError;
+use crate::EguiPainter;
+
+/// An offscreen target shown inside egui,
+/// render the game into `view` and it follows the size of its rect.
+///
+/// `show` it in update stage,then `resize` it before rendering into it
+pub struct EguiViewport {
+    texture_id : TextureId,
+    format : wgpu::TextureFormat,
+    texture : wgpu::Texture,
+    view : wgpu::TextureView,
+    size : (u32,u32),
+    // physical size of the rect shown in the last frame
+    wanted : (u32,u32),
+    rect : Rect,
+    events : Vec<egui::Event>,
+}
+
+fn create_target(
+    device : &wgpu::Device,
+    format : wgpu::TextureFormat,
+    size : (u32,u32)) -> (wgpu::Texture,wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("egui_viewport"),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (texture,view)
+}
+
+impl EguiViewport {
+    pub fn new(device : &wgpu::Device,painter : &mut EguiPainter,format : wgpu::TextureFormat) -> Self {
+        // the real size is unknown until it was shown
+        let size = (1,1);
+        let (texture,view) = create_target(device, format, size);
+        let texture_id = painter.textures().register(device, &texture, wgpu::FilterMode::Linear);
+        EguiViewport {
+            texture_id,
+            format,
+            texture,
+            view,
+            size,
+            wanted: size,
+            rect: Rect::NOTHING,
+            events: Vec::new(),
+        }
+    }
+
+    /// Fill the available space of `ui` with the viewport,
+    /// pointer events over it are collected for `drain_events`
+    pub fn show(&mut self,ui : &mut Ui) -> Response {
+        let (rect,response) = ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
+        egui::Image::new(self.texture_id, rect.size()).paint_at(ui, rect);
+
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        self.rect = rect;
+        self.wanted = (
+            ((rect.width() * pixels_per_point).round() as u32).max(1),
+            ((rect.height() * pixels_per_point).round() as u32).max(1));
+
+        // keep forwarding while dragging out of it
+        if response.hovered() || response.dragged() {
+            let to_local = |pos : egui::Pos2|((pos - rect.min) * pixels_per_point).to_pos2();
+            for event in ui.input().events.iter() {
+                let event = match event {
+                    egui::Event::PointerMoved(pos) => egui::Event::PointerMoved(to_local(*pos)),
+                    egui::Event::PointerButton { pos, button, pressed, modifiers } => egui::Event::PointerButton {
+                        pos: to_local(*pos),
+                        button: *button,
+                        pressed: *pressed,
+                        modifiers: *modifiers,
+                    },
+                    egui::Event::Scroll(delta) => egui::Event::Scroll(*delta * pixels_per_point),
+                    egui::Event::Zoom(factor) => egui::Event::Zoom(*factor),
+                    _ => continue
+                };
+                self.events.push(event);
+            }
+        }
+        response
+    }
+
+    /// Recreate the target if the rect changed its size,
+    /// returns `true` if it was recreated
+    pub fn resize(&mut self,device : &wgpu::Device,painter : &mut EguiPainter) -> Result<bool,BackendError> {
+        if self.wanted == self.size {
+            return Ok(false);
+        }
+        let (texture,view) = create_target(device, self.format, self.wanted);
+        painter.textures().update(device, self.texture_id, &texture, wgpu::FilterMode::Linear)?;
+        self.texture = texture;
+        self.view = view;
+        self.size = self.wanted;
+        Ok(true)
+    }
+
+    /// Pointer events since the last call,
+    /// positions are physical pixels from the top-left corner of the target
+    pub fn drain_events(&mut self) -> impl Iterator<Item = egui::Event> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn texture_id(&self) -> TextureId {
+        self.texture_id
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// Physical size of the target
+    pub fn size(&self) -> (u32,u32) {
+        self.size
+    }
+
+    /// Where it was shown in the last frame,in points
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Unregister the texture from egui
+    pub fn free(self,device : &wgpu::Device,painter : &mut EguiPainter) -> Result<(),BackendError> {
+        painter.textures().free(device, self.texture_id)
+    }
+}